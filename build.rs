@@ -0,0 +1,13 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/rspotd.proto");
+        // protox compiles the .proto in pure Rust, so building doesn't need protoc installed
+        let descriptors = protox::compile(["proto/rspotd.proto"], ["proto"])
+            .expect("proto/rspotd.proto should compile");
+        tonic_prost_build::configure()
+            .build_client(false)
+            .compile_fds(descriptors)
+            .expect("gRPC code generation should succeed");
+    }
+}
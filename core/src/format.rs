@@ -0,0 +1,1272 @@
+use crate::seed_fingerprint;
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike, Locale, NaiveDate, Utc,
+};
+#[cfg(feature = "pdf")]
+use printpdf::{BuiltinFont, CustomPdfConformance, Line, Mm, PdfConformance, PdfDocument, Point};
+use qrcode::{render::unicode::Dense1x2, QrCode};
+use rust_xlsxwriter::{DocProperties, Format, Workbook, XlsxError};
+use std::{cell::Cell, fmt::Write, io};
+
+/// Output formats accepted by --format and the config file
+pub const FORMATS: [&str; 15] = [
+    "ansible", "csv", "env", "html", "ics", "json", "markdown", "ndjson", "pdf", "qr", "table",
+    "text", "tsv", "xlsx", "yaml",
+];
+
+/// Whether a format is a binary document, which is written to a file or a pipe in one piece
+/// rather than rendered as text
+pub fn binary_format(format: &str) -> bool {
+    format == "xlsx" || format == "pdf"
+}
+
+/// Settings that control how generated passwords are rendered
+#[derive(Clone)]
+pub struct FormatOptions {
+    pub format: String,
+    pub date_format: String,
+    /// Language for month and day names in the date format, English if not set
+    pub locale: Option<Locale>,
+    pub header: bool,
+    pub title: Option<String>,
+    pub password_only: bool,
+    pub template: Option<String>,
+    pub color: bool,
+    /// Label each password with its seed's fingerprint, used when generating for several seeds
+    /// at once
+    pub grouped: bool,
+    /// Prefix env output with `export` for eval in a shell
+    pub export: bool,
+}
+
+/// Parse a locale given on the command line, e.g. de_DE or pt-BR
+pub fn locale_arg(locale: &str) -> Result<Locale, String> {
+    Locale::try_from(locale.replace('-', "_").as_str()).map_err(|_| {
+        format!(
+            "unknown locale '{}', expected a POSIX locale name such as de_DE or fr_FR",
+            locale
+        )
+    })
+}
+
+/// Placeholders available in --template
+pub const TEMPLATE_PLACEHOLDERS: [&str; 3] = ["date", "potd", "seed_hash"];
+
+/// A generated password, the date it belongs to and the seed it was generated with
+#[derive(Clone)]
+pub struct Entry {
+    pub seed: String,
+    pub date: NaiveDate,
+    pub potd: String,
+}
+
+// an entry with its date rendered using --date-format
+struct Row {
+    seed: Option<String>,
+    date: String,
+    potd: String,
+}
+
+/// Render the password for a single date, per seed
+pub fn format_potd(options: &FormatOptions, entries: &[Entry]) -> Result<String, String> {
+    format_output(options, entries, ": \t")
+}
+
+/// Render the passwords for a range of dates, grouped by seed
+pub fn format_potd_range(options: &FormatOptions, entries: &[Entry]) -> Result<String, String> {
+    format_output(options, entries, ": ")
+}
+
+// writing to memory can only fail if an entry can't be rendered
+fn format_output(
+    options: &FormatOptions,
+    entries: &[Entry],
+    separator: &str,
+) -> Result<String, String> {
+    let mut output = OutputWriter::new(options, Vec::new(), separator);
+    for entry in entries {
+        output.write(entry).map_err(|err| err.to_string())?;
+    }
+    let output = output.finish().map_err(|err| err.to_string())?;
+    let mut text = String::from_utf8(output).unwrap();
+    // the final newline, which is added back when the output is printed or saved
+    text.pop();
+    Ok(text)
+}
+
+/// Writes passwords to `out` in the chosen format as they are generated, so a long range
+/// never has to be held in memory. Tables and markdown need every row to size their
+/// columns, env output names its variables after how many there are and ansible output is a
+/// single object, so those are held back until `finish`.
+///
+/// An entry that can't be rendered, such as a QR code with more data than fits, fails with
+/// `io::ErrorKind::InvalidData` and a message saying why.
+pub struct OutputWriter<'a, W: io::Write> {
+    options: &'a FormatOptions,
+    out: W,
+    // the format actually written; --quiet wins over everything but qr
+    format: &'a str,
+    separator: &'a str,
+    // iCalendar lines end in CRLF
+    newline: &'static str,
+    stamp: String,
+    held: Vec<Entry>,
+    previous_seed: Option<String>,
+    entries: usize,
+    begun: bool,
+    written: bool,
+}
+
+impl<'a, W: io::Write> OutputWriter<'a, W> {
+    /// `separator` goes between date and password in text output
+    pub fn new(options: &'a FormatOptions, out: W, separator: &'a str) -> Self {
+        let format = match options.format.as_str() {
+            "qr" => "qr",
+            _ if options.password_only => "passwords",
+            format => format,
+        };
+        OutputWriter {
+            options,
+            out,
+            format,
+            separator,
+            newline: if format == "ics" { "\r\n" } else { "\n" },
+            stamp: Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+            held: Vec::new(),
+            previous_seed: None,
+            entries: 0,
+            begun: false,
+            written: false,
+        }
+    }
+
+    pub fn write(&mut self, entry: &Entry) -> io::Result<()> {
+        self.begin()?;
+        let options = self.options;
+        let new_seed =
+            options.grouped && self.previous_seed.as_deref() != Some(entry.seed.as_str());
+        match self.format {
+            "qr" => {
+                if self.entries > 0 {
+                    self.line("")?;
+                }
+                self.line(&qr_block(options, entry).map_err(invalid_data)?)?;
+            }
+            "passwords" => self.line(&entry.potd)?,
+            "text" => {
+                // a heading and blank line before each seed's group
+                if new_seed {
+                    if self.entries > 0 {
+                        self.line("")?;
+                    }
+                    self.line(&format!("Seed: {}", seed_fingerprint(&entry.seed)))?;
+                }
+                let line = text_line(options, entry, self.separator).map_err(invalid_data)?;
+                self.line(&line)?;
+            }
+            "ics" => {
+                for line in ics_event(options, entry, &self.stamp) {
+                    self.line(&ics_fold(&line))?;
+                }
+            }
+            "table" | "markdown" | "env" | "ansible" => self.held.push(entry.clone()),
+            format if binary_format(format) => {
+                return Err(invalid_data(format!(
+                    "{} is a binary format; write it to a file with --output, or pipe it",
+                    format
+                )))
+            }
+            format => {
+                let row = row(options, entry);
+                match format {
+                    "csv" => self.line(&csv_line(&row))?,
+                    "html" => self.line(&html_row(&row))?,
+                    "json" => {
+                        if self.entries == 0 {
+                            self.line("[")?;
+                        } else {
+                            self.out.write_all(b",")?;
+                        }
+                        self.line(&json_element(&row))?;
+                    }
+                    "ndjson" => self.line(&json_object(&row).to_string())?,
+                    "tsv" => self.line(&tsv_line(&row))?,
+                    _ => {
+                        if new_seed {
+                            self.line(&format!(
+                                "{}:",
+                                yaml_scalar(&seed_fingerprint(&entry.seed))
+                            ))?;
+                        }
+                        self.line(&yaml_line(&row))?;
+                    }
+                }
+            }
+        }
+        if new_seed {
+            self.previous_seed = Some(entry.seed.clone());
+        }
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Write anything held back and the closing lines, and flush
+    pub fn finish(mut self) -> io::Result<W> {
+        self.begin()?;
+        let options = self.options;
+        match self.format {
+            "table" => {
+                let table = format_table(&rows(options, &self.held), options.color);
+                self.line(&table)?;
+            }
+            "markdown" => {
+                let table = format_markdown(&rows(options, &self.held));
+                self.line(&table)?;
+            }
+            "env" => {
+                for line in env_lines(options, &self.held) {
+                    self.line(&line)?;
+                }
+            }
+            "ansible" => self.line(&format_ansible(options, &self.held))?,
+            "json" if self.entries == 0 => self.line("[]")?,
+            "json" => self.line("]")?,
+            "html" => {
+                for line in ["</tbody>", "</table>", "</body>", "</html>"] {
+                    self.line(line)?;
+                }
+            }
+            "ics" => self.line("END:VCALENDAR")?,
+            _ => {}
+        }
+        self.out.write_all(b"\n")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    // the lines that come before the first entry
+    fn begin(&mut self) -> io::Result<()> {
+        if self.begun {
+            return Ok(());
+        }
+        self.begun = true;
+        let headings = headings(self.options.grouped);
+        let lines = match self.format {
+            "csv" if self.options.header => vec![headings.join(",").to_lowercase()],
+            "tsv" if self.options.header => vec![headings.join("\t").to_lowercase()],
+            "html" => html_head(self.options.title.as_deref(), &headings),
+            "ics" => vec![
+                String::from("BEGIN:VCALENDAR"),
+                String::from("VERSION:2.0"),
+                String::from("PRODID:-//rspotd-cli//Password of the Day//EN"),
+                String::from("CALSCALE:GREGORIAN"),
+            ],
+            _ => Vec::new(),
+        };
+        for line in lines {
+            self.line(&line)?;
+        }
+        Ok(())
+    }
+
+    fn line(&mut self, line: &str) -> io::Result<()> {
+        if self.written {
+            self.out.write_all(self.newline.as_bytes())?;
+        }
+        self.written = true;
+        self.out.write_all(line.as_bytes())
+    }
+}
+
+/// The entries as a JSON array whatever --format is, e.g. for webhook payloads
+pub fn format_json_payload(options: &FormatOptions, entries: &[Entry]) -> String {
+    format_json(&rows(options, entries))
+}
+
+fn rows(options: &FormatOptions, entries: &[Entry]) -> Vec<Row> {
+    entries.iter().map(|entry| row(options, entry)).collect()
+}
+
+fn row(options: &FormatOptions, entry: &Entry) -> Row {
+    Row {
+        seed: options.grouped.then(|| seed_fingerprint(&entry.seed)),
+        date: format_date(options, entry.date),
+        potd: entry.potd.clone(),
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// `date: password`, or the line rendered from --template
+fn text_line(options: &FormatOptions, entry: &Entry, separator: &str) -> Result<String, String> {
+    match &options.template {
+        Some(template) => render_template(template, options, entry),
+        None => Ok(format!(
+            "{}{}{}",
+            paint_date(options, entry),
+            separator,
+            paint_potd(options, entry)
+        )),
+    }
+}
+
+pub fn format_date(options: &FormatOptions, date: NaiveDate) -> String {
+    localized_date(date, &options.date_format, options.locale)
+}
+
+pub fn localized_date(date: NaiveDate, date_format: &str, locale: Option<Locale>) -> String {
+    match locale {
+        Some(locale) => date.format_localized(date_format, locale).to_string(),
+        None => date.format(date_format).to_string(),
+    }
+}
+
+const STRFTIME_DOCS: &str = "https://docs.rs/chrono/latest/chrono/format/strftime/index.html";
+
+/// Fail if the date format contains specifiers that chrono doesn't know, or ones that need a
+/// time of day when only dates are formatted
+pub fn validate_date_format(date_format: &str) -> Result<(), String> {
+    for specifier in date_specifiers(date_format) {
+        if let Err(err) = check_specifier(&specifier) {
+            return Err(format!(
+                "Invalid date format '{}': {}. See {} for valid syntax.",
+                date_format, err, STRFTIME_DOCS
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Describe each specifier of a date format and show what it produces for the given date
+pub fn explain_date_format(date_format: &str, locale: Option<Locale>, date: NaiveDate) -> String {
+    let mut lines = vec![
+        format!("Format:  {}", date_format),
+        format!("Example: {}", localized_date(date, date_format, locale)),
+    ];
+    let specifiers = date_specifiers(date_format);
+    let width = specifiers
+        .iter()
+        .map(|specifier| specifier.len())
+        .max()
+        .unwrap_or_default();
+    if !specifiers.is_empty() {
+        lines.push(String::new());
+    }
+    for specifier in &specifiers {
+        lines.push(format!(
+            "{:width$}  {:50}  {:?}",
+            specifier,
+            describe_specifier(specifier),
+            localized_date(date, specifier, locale),
+        ));
+    }
+    lines.join("\n")
+}
+
+// every %-specifier in a date format along with its modifiers, e.g. %d, %-m or %%
+fn date_specifiers(date_format: &str) -> Vec<String> {
+    let mut specifiers = Vec::new();
+    let mut chars = date_format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        let mut specifier = String::from('%');
+        for next in chars.by_ref() {
+            specifier.push(next);
+            if !matches!(next, '-' | '_' | '0' | '^' | '#' | '.' | ':' | '1'..='9') {
+                break;
+            }
+        }
+        specifiers.push(specifier);
+    }
+    specifiers
+}
+
+fn check_specifier(specifier: &str) -> Result<(), String> {
+    if StrftimeItems::new(specifier).any(|item| item == Item::Error) {
+        return Err(format!("unknown specifier {}", specifier));
+    }
+    let mut output = String::new();
+    if write!(output, "{}", NaiveDate::default().format(specifier)).is_err() {
+        return Err(format!(
+            "{} needs a time of day, but only dates are formatted",
+            specifier
+        ));
+    }
+    Ok(())
+}
+
+fn describe_specifier(specifier: &str) -> String {
+    let description = match specifier.chars().last() {
+        Some('Y') => "year",
+        Some('C') => "century (year / 100)",
+        Some('y') => "year within the century (00-99)",
+        Some('q') => "quarter of the year (1-4)",
+        Some('m') => "month (01-12)",
+        Some('b') | Some('h') => "abbreviated month name",
+        Some('B') => "full month name",
+        Some('d') => "day of the month (01-31)",
+        Some('e') => "day of the month, space-padded ( 1-31)",
+        Some('a') => "abbreviated weekday name",
+        Some('A') => "full weekday name",
+        Some('w') => "day of the week, Sunday = 0",
+        Some('u') => "day of the week, Monday = 1",
+        Some('U') => "week of the year, starting on Sunday",
+        Some('W') => "week of the year, starting on Monday",
+        Some('G') => "ISO 8601 week-based year",
+        Some('g') => "ISO 8601 week-based year within the century",
+        Some('V') => "ISO 8601 week number (01-53)",
+        Some('j') => "day of the year (001-366)",
+        Some('D') => "month/day/year, same as %m/%d/%y",
+        Some('x') => "the locale's date representation",
+        Some('F') => "year-month-day, same as %Y-%m-%d",
+        Some('v') => "day-month-year, same as %e-%b-%Y",
+        Some('%') => "a literal %",
+        Some('n') => "a newline",
+        Some('t') => "a tab",
+        _ => "",
+    };
+    let modifier = match specifier.chars().nth(1) {
+        Some('-') if specifier.len() > 2 => ", without padding",
+        Some('_') if specifier.len() > 2 => ", padded with spaces",
+        Some('0') if specifier.len() > 2 => ", padded with zeros",
+        _ => "",
+    };
+    format!("{}{}", description, modifier)
+}
+
+// text output dims the date and highlights the password when color is enabled
+fn paint_date(options: &FormatOptions, entry: &Entry) -> String {
+    let date = format_date(options, entry.date);
+    if options.color {
+        format!("\x1b[2m{}\x1b[0m", date)
+    } else {
+        date
+    }
+}
+
+fn paint_potd(options: &FormatOptions, entry: &Entry) -> String {
+    if options.color {
+        format!("\x1b[1;36m{}\x1b[0m", entry.potd)
+    } else {
+        entry.potd.clone()
+    }
+}
+
+/// Render (profile, DES) pairs; formats without a natural two-column form fall back to aligned text
+pub fn format_des_table(format: &str, rows: &[(String, String)]) -> String {
+    let objects = rows
+        .iter()
+        .map(|(profile, des)| serde_json::json!({ "profile": profile, "des": des }));
+    let lines: Vec<String> = match format {
+        "json" => return serde_json::to_string_pretty(&objects.collect::<Vec<_>>()).unwrap(),
+        "ndjson" => objects.map(|object| object.to_string()).collect(),
+        "yaml" => rows
+            .iter()
+            .map(|(profile, des)| format!("{}: {}", yaml_scalar(profile), yaml_scalar(des)))
+            .collect(),
+        "csv" => rows
+            .iter()
+            .map(|(profile, des)| format!("{},{}", csv_field(profile), csv_field(des)))
+            .collect(),
+        "tsv" => rows
+            .iter()
+            .map(|(profile, des)| format!("{}\t{}", tsv_field(profile), tsv_field(des)))
+            .collect(),
+        _ => {
+            let width = rows
+                .iter()
+                .map(|(profile, _)| profile.chars().count())
+                .max()
+                .unwrap_or(0);
+            rows.iter()
+                .map(|(profile, des)| format!("{:<width$}  {}", profile, des))
+                .collect()
+        }
+    };
+    lines.join("\n")
+}
+
+/// A password for one device, profile or the top-level seed, for `fleet`
+pub struct FleetRow {
+    pub name: String,
+    /// "default", "profile" or "device"
+    pub kind: &'static str,
+    pub host: Option<String>,
+    pub seed_id: String,
+    pub potd: String,
+}
+
+/// The formats `fleet` can render, see [`format_fleet`]
+pub const FLEET_FORMATS: [&str; 7] = ["text", "table", "json", "ndjson", "yaml", "csv", "tsv"];
+
+/// Render every row's password for the date as a single document; text and table are an
+/// aligned table under the date
+pub fn format_fleet(format: &str, date: NaiveDate, rows: &[FleetRow], header: bool) -> String {
+    let date = date.format("%Y-%m-%d").to_string();
+    let object = |row: &FleetRow| {
+        serde_json::json!({
+            "name": row.name,
+            "kind": row.kind,
+            "host": row.host,
+            "seed_id": row.seed_id,
+            "password": row.potd,
+        })
+    };
+    let cells = |row: &FleetRow| {
+        [
+            row.name.clone(),
+            row.kind.to_string(),
+            row.host.clone().unwrap_or_default(),
+            row.seed_id.clone(),
+            row.potd.clone(),
+        ]
+    };
+    let headings = ["name", "kind", "host", "seed_id", "password"].map(String::from);
+    let mut lines: Vec<String> = Vec::new();
+    match format {
+        "json" => {
+            let passwords: Vec<_> = rows.iter().map(object).collect();
+            let document = serde_json::json!({ "date": date, "passwords": passwords });
+            return serde_json::to_string_pretty(&document).unwrap();
+        }
+        "ndjson" => {
+            for row in rows {
+                let mut object = object(row);
+                object["date"] = serde_json::Value::from(date.as_str());
+                lines.push(object.to_string());
+            }
+        }
+        "yaml" => {
+            lines.push(format!("date: {}", yaml_scalar(&date)));
+            lines.push(String::from("passwords:"));
+            for row in rows {
+                let fields: Vec<String> = headings
+                    .iter()
+                    .zip(cells(row))
+                    .filter(|(heading, _)| *heading != "host" || row.host.is_some())
+                    .map(|(heading, value)| format!("{}: {}", heading, yaml_scalar(&value)))
+                    .collect();
+                lines.push(format!("  - {}", fields.join("\n    ")));
+            }
+        }
+        "csv" | "tsv" => {
+            let (separator, field): (&str, fn(&str) -> String) = match format {
+                "csv" => (",", csv_field),
+                _ => ("\t", tsv_field),
+            };
+            let join = |cells: [String; 5]| {
+                let fields: Vec<String> = cells.iter().map(|cell| field(cell)).collect();
+                fields.join(separator)
+            };
+            if header {
+                lines.push(join(headings.clone()));
+            }
+            lines.extend(rows.iter().map(|row| join(cells(row))));
+        }
+        _ => {
+            let mut table = vec![headings.map(|heading| heading.to_uppercase())];
+            table.extend(rows.iter().map(cells));
+            let widths: Vec<usize> = (0..4)
+                .map(|column| {
+                    table
+                        .iter()
+                        .map(|row| row[column].chars().count())
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+            lines.push(format!("Passwords of the day for {}", date));
+            lines.push(String::new());
+            for row in &table {
+                let mut line = String::new();
+                for (cell, width) in row.iter().zip(&widths) {
+                    line.push_str(&format!("{:<width$}  ", cell, width = width));
+                }
+                line.push_str(&row[4]);
+                lines.push(line);
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Fail if the template contains unknown placeholders or unbalanced braces
+pub fn validate_template(template: &str) -> Result<(), String> {
+    expand_template(template, &TEMPLATE_PLACEHOLDERS, |name| {
+        TEMPLATE_PLACEHOLDERS.contains(&name).then(String::new)
+    })
+    .map(|_| ())
+    .map_err(|err| format!("Invalid template '{}': {}", template, err))
+}
+
+fn render_template(
+    template: &str,
+    options: &FormatOptions,
+    entry: &Entry,
+) -> Result<String, String> {
+    expand_template(template, &TEMPLATE_PLACEHOLDERS, |name| match name {
+        "date" => Some(paint_date(options, entry)),
+        "potd" => Some(paint_potd(options, entry)),
+        "seed_hash" => Some(seed_fingerprint(&entry.seed)),
+        _ => None,
+    })
+    .map_err(|err| format!("Invalid template '{}': {}", template, err))
+}
+
+/// Whether an --output path has placeholders, i.e. {date}, so that each date's passwords go to
+/// a file of their own
+pub fn per_date_output(path: &str) -> bool {
+    let placeholders = Cell::new(false);
+    let _ = expand_template(path, &["date"], |_| {
+        placeholders.set(true);
+        Some(String::new())
+    });
+    placeholders.get()
+}
+
+/// The --output path for one date. {date} is always YYYY-MM-DD, whatever --date-format says,
+/// so the files sort by date and the name never gains a slash.
+pub fn output_path(path: &str, date: NaiveDate) -> Result<String, String> {
+    expand_template(path, &["date"], |name| {
+        (name == "date").then(|| date.format("%Y-%m-%d").to_string())
+    })
+    .map_err(|err| format!("Invalid output path '{}': {}", path, err))
+}
+
+// substitute {name} placeholders using the given lookup; {{ and }} produce literal braces
+fn expand_template(
+    template: &str,
+    placeholders: &[&str],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut output = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '{' && chars.peek() == Some(&'{') {
+            chars.next();
+            output.push('{');
+        } else if c == '}' && chars.peek() == Some(&'}') {
+            chars.next();
+            output.push('}');
+        } else if c == '{' {
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) => name.push(c),
+                    None => return Err(String::from("unclosed '{'")),
+                }
+            }
+            match lookup(&name) {
+                Some(value) => output.push_str(&value),
+                None => {
+                    return Err(format!(
+                        "unknown placeholder '{{{}}}', expected one of {{{}}}",
+                        name,
+                        placeholders.join("}, {")
+                    ))
+                }
+            }
+        } else if c == '}' {
+            return Err(String::from("unmatched '}', use '}}' for a literal brace"));
+        } else {
+            output.push(c);
+        }
+    }
+    Ok(output)
+}
+
+// column headings for the tabular formats; the seed column is only present when grouped
+fn headings(grouped: bool) -> Vec<&'static str> {
+    if grouped {
+        vec!["Seed", "Date", "Password"]
+    } else {
+        vec!["Date", "Password"]
+    }
+}
+
+fn cells(row: &Row) -> Vec<&str> {
+    let mut cells: Vec<&str> = Vec::new();
+    if let Some(seed) = &row.seed {
+        cells.push(seed);
+    }
+    cells.push(&row.date);
+    cells.push(&row.potd);
+    cells
+}
+
+// headings and cells for the tables that are rendered in one go
+fn columns(rows: &[Row]) -> (Vec<&'static str>, Vec<Vec<&str>>) {
+    let grouped = rows.iter().any(|row| row.seed.is_some());
+    (headings(grouped), rows.iter().map(cells).collect())
+}
+
+// the widest value in each column, including its heading
+fn column_widths(headings: &[&str], cells: &[Vec<String>]) -> Vec<usize> {
+    (0..headings.len())
+        .map(|column| {
+            cells
+                .iter()
+                .map(|row| row[column].chars().count())
+                .chain([headings[column].chars().count()])
+                .max()
+                .unwrap()
+        })
+        .collect()
+}
+
+fn csv_line(row: &Row) -> String {
+    let fields: Vec<String> = cells(row).iter().map(|value| csv_field(value)).collect();
+    fields.join(",")
+}
+
+// quote fields that would otherwise break the row, e.g. a date format containing commas
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// a boxed table with padded columns, so variable-width dates stay aligned
+fn format_table(rows: &[Row], color: bool) -> String {
+    let (headings, cells) = columns(rows);
+    let cells: Vec<Vec<String>> = cells
+        .iter()
+        .map(|row| row.iter().map(|value| value.to_string()).collect())
+        .collect();
+    let widths = column_widths(&headings, &cells);
+    let border = |left: &str, middle: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|width| "─".repeat(width + 2)).collect();
+        format!("{}{}{}", left, segments.join(middle), right)
+    };
+    let line = |values: Vec<String>| format!("│ {} │", values.join(" │ "));
+    let mut lines: Vec<String> = Vec::new();
+    lines.push(border("┌", "┬", "┐"));
+    lines.push(line(
+        headings
+            .iter()
+            .zip(&widths)
+            .map(|(heading, width)| format!("{:<width$}", heading))
+            .collect(),
+    ));
+    lines.push(border("├", "┼", "┤"));
+    let last = headings.len() - 1;
+    for row in &cells {
+        lines.push(line(
+            row.iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(column, (value, width))| {
+                    let padded = format!("{:<width$}", value);
+                    if !color {
+                        padded
+                    } else if column == last {
+                        format!("\x1b[1;36m{}\x1b[0m", padded)
+                    } else if column == last - 1 {
+                        format!("\x1b[2m{}\x1b[0m", padded)
+                    } else {
+                        padded
+                    }
+                })
+                .collect(),
+        ));
+    }
+    lines.push(border("└", "┴", "┘"));
+    lines.join("\n")
+}
+
+fn tsv_line(row: &Row) -> String {
+    let fields: Vec<String> = cells(row).iter().map(|value| tsv_field(value)).collect();
+    fields.join("\t")
+}
+
+// TSV has no quoting, so separators inside a field are replaced with spaces
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+// a GitHub-flavored table, padded so it also reads well before being rendered
+fn format_markdown(rows: &[Row]) -> String {
+    let (headings, cells) = columns(rows);
+    let cells: Vec<Vec<String>> = cells
+        .iter()
+        .map(|row| row.iter().map(|value| value.replace('|', "\\|")).collect())
+        .collect();
+    let widths = column_widths(&headings, &cells);
+    let line = |values: Vec<String>| format!("| {} |", values.join(" | "));
+    let mut lines: Vec<String> = Vec::new();
+    lines.push(line(
+        headings
+            .iter()
+            .zip(&widths)
+            .map(|(heading, width)| format!("{:<width$}", heading))
+            .collect(),
+    ));
+    let rules: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+    lines.push(format!("|{}|", rules.join("|")));
+    for row in &cells {
+        lines.push(line(
+            row.iter()
+                .zip(&widths)
+                .map(|(value, width)| format!("{:<width$}", value))
+                .collect(),
+        ));
+    }
+    lines.join("\n")
+}
+
+// the start of a standalone page with a single table, styled to print cleanly
+fn html_head(title: Option<&str>, headings: &[&str]) -> Vec<String> {
+    let title = html_escape(title.unwrap_or("Password of the Day"));
+    let heading_cells: Vec<String> = headings
+        .iter()
+        .map(|heading| format!("<th>{}</th>", heading))
+        .collect();
+    vec![
+        String::from("<!DOCTYPE html>"),
+        String::from("<html>"),
+        String::from("<head>"),
+        String::from("<meta charset=\"utf-8\">"),
+        format!("<title>{}</title>", title),
+        String::from("<style>"),
+        String::from("body { font-family: sans-serif; }"),
+        String::from("table { border-collapse: collapse; }"),
+        String::from("th, td { border: 1px solid #000; padding: 0.3em 1em; text-align: left; }"),
+        String::from("td.password { font-family: monospace; font-size: 1.2em; }"),
+        String::from("</style>"),
+        String::from("</head>"),
+        String::from("<body>"),
+        format!("<h1>{}</h1>", title),
+        String::from("<table>"),
+        format!("<thead><tr>{}</tr></thead>", heading_cells.concat()),
+        String::from("<tbody>"),
+    ]
+}
+
+fn html_row(row: &Row) -> String {
+    let cells = cells(row);
+    let last = cells.len() - 1;
+    let row_cells: Vec<String> = cells
+        .iter()
+        .enumerate()
+        .map(|(column, value)| {
+            if column == last {
+                format!("<td class=\"password\">{}</td>", html_escape(value))
+            } else {
+                format!("<td>{}</td>", html_escape(value))
+            }
+        })
+        .collect();
+    format!("<tr>{}</tr>", row_cells.concat())
+}
+
+pub fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// {"date": ..., "password": ...}, with a leading "seed" key when grouped
+fn json_object(row: &Row) -> serde_json::Value {
+    match &row.seed {
+        Some(seed) => serde_json::json!({ "seed": seed, "date": row.date, "password": row.potd }),
+        None => serde_json::json!({ "date": row.date, "password": row.potd }),
+    }
+}
+
+// an array of {"date": ..., "password": ...} objects
+fn format_json(rows: &[Row]) -> String {
+    let entries: Vec<serde_json::Value> = rows.iter().map(json_object).collect();
+    serde_json::to_string_pretty(&entries).unwrap()
+}
+
+// an object as it appears in the pretty-printed array
+fn json_element(row: &Row) -> String {
+    let object = serde_json::to_string_pretty(&json_object(row)).unwrap();
+    let lines: Vec<String> = object.lines().map(|line| format!("  {}", line)).collect();
+    lines.join("\n")
+}
+
+// {"potd": {"2024-07-01": "..."}} for Ansible vars, with a level for each seed when grouped,
+// e.g. {"potd": {"99b3bcf6": {"2024-07-01": "..."}}}; dates are always YYYY-MM-DD so playbooks can
+// look them up whatever --date-format says
+fn format_ansible(options: &FormatOptions, entries: &[Entry]) -> String {
+    let mut potd = serde_json::Map::new();
+    for entry in entries {
+        let date = entry.date.format("%Y-%m-%d").to_string();
+        let password = serde_json::Value::from(entry.potd.as_str());
+        if !options.grouped {
+            potd.insert(date, password);
+            continue;
+        }
+        let seed = potd
+            .entry(seed_fingerprint(&entry.seed))
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+        if let serde_json::Value::Object(dates) = seed {
+            dates.insert(date, password);
+        }
+    }
+    serde_json::to_string_pretty(&serde_json::json!({ "potd": potd })).unwrap()
+}
+
+// date: password, indented under the seed when grouped; all scalars are double-quoted so
+// YAML parsers don't turn dates into timestamps or passwords into numbers
+fn yaml_line(row: &Row) -> String {
+    let indent = if row.seed.is_some() { "  " } else { "" };
+    format!(
+        "{}{}: {}",
+        indent,
+        yaml_scalar(&row.date),
+        yaml_scalar(&row.potd)
+    )
+}
+
+// a JSON string is also a valid double-quoted YAML scalar
+fn yaml_scalar(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+// POTD and POTD_DATE for a single password; otherwise one variable per password, named after
+// its seed's fingerprint when there are several seeds and its date when there are several
+// dates, e.g. POTD_99B3BCF6_20240421
+fn env_lines(options: &FormatOptions, entries: &[Entry]) -> Vec<String> {
+    let prefix = if options.export { "export " } else { "" };
+    let single_date = entries.iter().all(|entry| entry.date == entries[0].date);
+    let mut lines: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            let mut name = String::from("POTD");
+            if options.grouped {
+                name.push('_');
+                name.push_str(&seed_fingerprint(&entry.seed).to_uppercase());
+            }
+            if !single_date {
+                name.push_str(&entry.date.format("_%Y%m%d").to_string());
+            }
+            format!("{}{}={}", prefix, name, env_value(&entry.potd))
+        })
+        .collect();
+    if single_date && !entries.is_empty() {
+        let date = format_date(options, entries[0].date);
+        lines.push(format!("{}POTD_DATE={}", prefix, env_value(&date)));
+    }
+    lines
+}
+
+// single-quoted if it holds anything a shell would treat specially, e.g. the spaces of a
+// --date-format
+fn env_value(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.,:/+@%".contains(c));
+    if plain && !value.is_empty() {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+// an all-day event for the date; the dates are written in the calendar's own format, so
+// --date-format only applies to the other formats
+fn ics_event(options: &FormatOptions, entry: &Entry, stamp: &str) -> Vec<String> {
+    let title = options.title.as_deref().unwrap_or("Password of the Day");
+    let day = entry.date.format("%Y%m%d");
+    let next_day = entry.date.succ_opt().unwrap_or(entry.date).format("%Y%m%d");
+    let summary = if options.grouped {
+        format!("{} ({})", title, seed_fingerprint(&entry.seed))
+    } else {
+        title.to_string()
+    };
+    vec![
+        String::from("BEGIN:VEVENT"),
+        format!("UID:{}-{}@rspotd-cli", day, seed_fingerprint(&entry.seed)),
+        format!("DTSTAMP:{}", stamp),
+        format!("DTSTART;VALUE=DATE:{}", day),
+        format!("DTEND;VALUE=DATE:{}", next_day),
+        format!("SUMMARY:{}", ics_text(&summary)),
+        format!("DESCRIPTION:Password: {}", ics_text(&entry.potd)),
+        String::from("TRANSP:TRANSPARENT"),
+        String::from("END:VEVENT"),
+    ]
+}
+
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// content lines longer than 75 octets are continued on the next line after a space
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+// a QR code drawn with half-block characters and labeled with the date unless only
+// passwords are wanted
+fn qr_block(options: &FormatOptions, entry: &Entry) -> Result<String, String> {
+    let code = qr_code(options, entry)?;
+    // light modules are drawn in the foreground color, which suits dark terminals
+    let image = code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build();
+    Ok(if options.password_only {
+        image
+    } else if options.grouped {
+        format!(
+            "{} ({})\n{}",
+            format_date(options, entry.date),
+            seed_fingerprint(&entry.seed),
+            image
+        )
+    } else {
+        format!("{}\n{}", format_date(options, entry.date), image)
+    })
+}
+
+/// The entries rendered in a binary format, see [`binary_format`]
+pub fn format_document(options: &FormatOptions, entries: &[Entry]) -> Result<Vec<u8>, String> {
+    match options.format.as_str() {
+        "xlsx" => format_xlsx(options, entries),
+        #[cfg(feature = "pdf")]
+        "pdf" => format_pdf(options, entries),
+        #[cfg(not(feature = "pdf"))]
+        "pdf" => Err(String::from("pdf output isn't available in this build")),
+        format => Err(format!("{} is not a binary format", format)),
+    }
+}
+
+/// A spreadsheet of the entries with a sheet for each month, named after it, and a frozen
+/// heading row
+pub fn format_xlsx(options: &FormatOptions, entries: &[Entry]) -> Result<Vec<u8>, String> {
+    let failed = |err: XlsxError| format!("Unable to build spreadsheet: {}", err);
+    let mut workbook = Workbook::new();
+    if let Some(title) = &options.title {
+        workbook.set_properties(&DocProperties::new().set_title(title));
+    }
+    let heading = Format::new().set_bold();
+    // so that 0 and O, or 1 and l, can be told apart
+    let password = Format::new().set_font_name("Consolas");
+    let headings = headings(options.grouped);
+    for entries in group(entries, |entry| (entry.date.year(), entry.date.month())) {
+        let sheet = workbook.add_worksheet();
+        let name = localized_date(entries[0].date, "%B %Y", options.locale);
+        sheet.set_name(name).map_err(failed)?;
+        for (column, text) in headings.iter().enumerate() {
+            sheet
+                .write_string_with_format(0, column as u16, *text, &heading)
+                .map_err(failed)?;
+        }
+        for (line, entry) in entries.iter().enumerate() {
+            let row = row(options, entry);
+            let cells = cells(&row);
+            let last = cells.len() - 1;
+            for (column, text) in cells.into_iter().enumerate() {
+                let line = line as u32 + 1;
+                if column == last {
+                    sheet.write_string_with_format(line, column as u16, text, &password)
+                } else {
+                    sheet.write_string(line, column as u16, text)
+                }
+                .map_err(failed)?;
+            }
+        }
+        sheet.set_freeze_panes(1, 0).map_err(failed)?;
+        sheet.autofit();
+    }
+    if entries.is_empty() {
+        workbook.add_worksheet();
+    }
+    workbook.save_to_buffer().map_err(failed)
+}
+
+/// A printable sheet of the entries with a page for each month, in large type, for each seed
+/// in turn
+#[cfg(feature = "pdf")]
+pub fn format_pdf(options: &FormatOptions, entries: &[Entry]) -> Result<Vec<u8>, String> {
+    // US Letter, which also fits on A4 with the margins
+    const WIDTH: f32 = 215.9;
+    const HEIGHT: f32 = 279.4;
+    const SIZE: f32 = 16.0;
+    const ROW: f32 = 7.2;
+    // every character of Courier is 0.6 em wide, in mm
+    const CHAR: f32 = SIZE * 0.6 * 25.4 / 72.0;
+    const GAP: f32 = 12.0;
+    let failed = |err: printpdf::Error| format!("Unable to build PDF: {}", err);
+    let pages = group(entries, |entry| {
+        (entry.date.year(), entry.date.month(), entry.seed.as_str())
+    });
+    let title = options.title.as_deref().unwrap_or("Password of the Day");
+    let (document, page, layer) = PdfDocument::new(title, Mm(WIDTH), Mm(HEIGHT), "Passwords");
+    let document = document.with_conformance(PdfConformance::Custom(CustomPdfConformance {
+        requires_icc_profile: false,
+        requires_xmp_metadata: false,
+        ..Default::default()
+    }));
+    let bold = document
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(failed)?;
+    let regular = document
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(failed)?;
+    // monospaced, so the columns line up and 0 and O, or 1 and l, can be told apart
+    let mono = document
+        .add_builtin_font(BuiltinFont::Courier)
+        .map_err(failed)?;
+    let mono_bold = document
+        .add_builtin_font(BuiltinFont::CourierBold)
+        .map_err(failed)?;
+    let headings = ["Date", "Password"];
+    for (index, entries) in pages.iter().enumerate() {
+        let layer = if index == 0 {
+            document.get_page(page).get_layer(layer)
+        } else {
+            let (page, layer) = document.add_page(Mm(WIDTH), Mm(HEIGHT), "Passwords");
+            document.get_page(page).get_layer(layer)
+        };
+        let rows: Vec<[String; 2]> = entries
+            .iter()
+            .map(|entry| [format_date(options, entry.date), entry.potd.clone()])
+            .collect();
+        let date_width = rows
+            .iter()
+            .map(|row| row[0].chars().count())
+            .chain([headings[0].len()])
+            .max()
+            .unwrap_or_default() as f32
+            * CHAR;
+        let potd_width = rows
+            .iter()
+            .map(|row| row[1].chars().count())
+            .chain([headings[1].len()])
+            .max()
+            .unwrap_or_default() as f32
+            * CHAR;
+        let left = ((WIDTH - date_width - GAP - potd_width) / 2.0).max(15.0);
+        let right = (left + date_width + GAP + potd_width).min(WIDTH - 15.0);
+        let mut y = HEIGHT - 20.0;
+        layer.use_text(title, 22.0, Mm(left), Mm(y), &bold);
+        y -= 8.0;
+        let month = localized_date(entries[0].date, "%B %Y", options.locale);
+        let subtitle = if options.grouped {
+            format!("{}, seed {}", month, seed_fingerprint(&entries[0].seed))
+        } else {
+            month
+        };
+        layer.use_text(subtitle, SIZE, Mm(left), Mm(y), &regular);
+        y -= 12.0;
+        layer.use_text(headings[0], SIZE, Mm(left), Mm(y), &mono_bold);
+        layer.use_text(
+            headings[1],
+            SIZE,
+            Mm(left + date_width + GAP),
+            Mm(y),
+            &mono_bold,
+        );
+        layer.set_outline_thickness(1.0);
+        layer.add_line(rule(left, right, y - 2.2));
+        layer.set_outline_thickness(0.3);
+        for row in &rows {
+            y -= ROW;
+            layer.use_text(&row[0], SIZE, Mm(left), Mm(y), &mono);
+            layer.use_text(&row[1], SIZE, Mm(left + date_width + GAP), Mm(y), &mono);
+            layer.add_line(rule(left, right, y - 2.2));
+        }
+    }
+    document.save_to_bytes().map_err(failed)
+}
+
+// the entries split up by key, in the order each key first appears
+fn group<'a, K: PartialEq>(
+    entries: &'a [Entry],
+    key: impl Fn(&'a Entry) -> K,
+) -> Vec<Vec<&'a Entry>> {
+    let mut groups: Vec<(K, Vec<&Entry>)> = Vec::new();
+    for entry in entries {
+        let entry_key = key(entry);
+        match groups.iter_mut().find(|(key, _)| *key == entry_key) {
+            Some((_, group)) => group.push(entry),
+            None => groups.push((entry_key, vec![entry])),
+        }
+    }
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+// a horizontal line across the table at height y
+#[cfg(feature = "pdf")]
+fn rule(left: f32, right: f32, y: f32) -> Line {
+    Line {
+        points: vec![
+            (Point::new(Mm(left), Mm(y)), false),
+            (Point::new(Mm(right), Mm(y)), false),
+        ],
+        is_closed: false,
+    }
+}
+
+/// The QR code for a single entry as a PNG image
+pub fn format_qr_png(options: &FormatOptions, entry: &Entry) -> Result<Vec<u8>, String> {
+    // pixels per module, and modules of quiet zone around the code
+    const SCALE: usize = 8;
+    const BORDER: usize = 4;
+    let code = qr_code(options, entry)?;
+    let modules = code.width();
+    let colors = code.to_colors();
+    let size = (modules + 2 * BORDER) * SCALE;
+    let mut pixels = vec![255u8; size * size];
+    for (index, color) in colors.iter().enumerate() {
+        if *color == qrcode::Color::Light {
+            continue;
+        }
+        let (x, y) = (index % modules + BORDER, index / modules + BORDER);
+        for row in y * SCALE..(y + 1) * SCALE {
+            pixels[row * size + x * SCALE..row * size + (x + 1) * SCALE].fill(0);
+        }
+    }
+    let mut image = Vec::new();
+    let mut encoder = png::Encoder::new(&mut image, size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|err| format!("Unable to encode QR code image: {}", err))?;
+    Ok(image)
+}
+
+// the password, or the line rendered from --template so the code can carry the date as well
+fn qr_code(options: &FormatOptions, entry: &Entry) -> Result<QrCode, String> {
+    let payload = match &options.template {
+        Some(template) => expand_template(template, &TEMPLATE_PLACEHOLDERS, |name| match name {
+            "date" => Some(format_date(options, entry.date)),
+            "potd" => Some(entry.potd.clone()),
+            "seed_hash" => Some(seed_fingerprint(&entry.seed)),
+            _ => None,
+        })
+        .map_err(|err| format!("Invalid template '{}': {}", template, err))?,
+        None => entry.potd.clone(),
+    };
+    QrCode::new(payload.as_bytes()).map_err(|err| format!("Unable to encode QR code: {}", err))
+}
@@ -0,0 +1,79 @@
+//! The parts of rspotd-cli that don't need an operating system, shared by the command line
+//! and its WebAssembly and C bindings: [`range`] generates a range of any length a year at a
+//! time, the checks tell why a seed or date can't be used before rspotd panics on it, and,
+//! with the `std` feature, [`format`] renders passwords in the command line's output formats.
+//!
+//! Without the `std` feature the crate itself is `no_std`, though rspotd still needs std.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod format;
+pub mod range;
+
+use alloc::{format, string::String};
+use chrono::{Datelike, NaiveDate};
+use rspotd::vals::DEFAULT_SEED;
+use sha2::{Digest, Sha256};
+
+/// Short, non-reversible identifier for a seed, so output can reference which seed
+/// produced it without disclosing the seed itself
+pub fn seed_fingerprint(seed: &str) -> String {
+    let digest = Sha256::digest(seed.as_bytes());
+    digest[..4]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Explain exactly what is wrong with a seed the ARRIS algorithm can't use and how to fix it
+pub fn validate_seed(seed: &str) -> Result<(), String> {
+    if seed == DEFAULT_SEED {
+        return Ok(());
+    }
+    // rspotd checks only the length, but panics on a seed it can't index byte by byte
+    if let Some((position, c)) = seed.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+        return Err(format!(
+            "Invalid seed: character '{}' at position {} is not allowed. Seeds may only contain ASCII characters; remove or replace it.",
+            c,
+            position + 1
+        ));
+    }
+    let length = seed.len();
+    if length < 4 {
+        return Err(format!(
+            "Invalid seed: too short ({} characters). Seeds must be 4-8 characters long; add {} more.",
+            length,
+            4 - length
+        ));
+    }
+    if length > 8 {
+        return Err(format!(
+            "Invalid seed: too long ({} characters). Seeds must be 4-8 characters long; remove {}.",
+            length,
+            length - 8
+        ));
+    }
+    Ok(())
+}
+
+/// Explain why there's no password for a date, given as `input`, if there isn't: rspotd takes
+/// the last two digits of a year that has at least three and panics on any other, and only
+/// accepts years of four digits
+pub fn validate_date(date: NaiveDate, input: &str) -> Result<(), String> {
+    if date.year() < 100 {
+        return Err(format!(
+            "Unable to generate a password for '{}', the year must be 100 or later.",
+            input
+        ));
+    }
+    if date.year() > 9999 {
+        return Err(format!(
+            "Unable to generate a password for '{}', the year must be 9999 or earlier.",
+            input
+        ));
+    }
+    Ok(())
+}
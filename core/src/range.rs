@@ -0,0 +1,61 @@
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use chrono::{Duration, NaiveDate};
+use core::error::Error;
+
+/// The spans of up to a year, inclusive, that the range from start to end is generated in,
+/// since the rspotd library refuses anything longer; none if end is before start
+pub fn chunks(start: NaiveDate, end: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut chunks = Vec::new();
+    let mut chunk_start = start;
+    while chunk_start <= end {
+        let chunk_end = chunk_end(chunk_start, end);
+        chunks.push((chunk_start, chunk_end));
+        chunk_start = chunk_end + Duration::days(1);
+    }
+    chunks
+}
+
+/// The last date of the chunk that starts on start, for a range ending on end
+pub fn chunk_end(start: NaiveDate, end: NaiveDate) -> NaiveDate {
+    end.min(start + Duration::days(365))
+}
+
+/// The ARRIS password for every date from start to end, inclusive, a chunk at a time
+pub fn generate_range(
+    start: NaiveDate,
+    end: NaiveDate,
+    seed: &str,
+) -> Result<Vec<(NaiveDate, String)>, Box<dyn Error>> {
+    let mut passwords = Vec::new();
+    for (chunk_start, chunk_end) in chunks(start, end) {
+        passwords.extend(generate_chunk(chunk_start, chunk_end, seed)?);
+    }
+    Ok(passwords)
+}
+
+/// The ARRIS password for every date of one of the [`chunks`].
+///
+/// `rspotd::generate` validates its input with a freshly compiled regex on every call, so
+/// the chunk is generated with `generate_multiple` instead.
+pub fn generate_chunk(
+    start: NaiveDate,
+    end: NaiveDate,
+    seed: &str,
+) -> Result<Vec<(NaiveDate, String)>, Box<dyn Error>> {
+    let begin = start.format("%Y-%m-%d").to_string();
+    if end == start {
+        // generate_multiple refuses a range of a single day
+        return Ok(vec![(start, rspotd::generate(&begin, seed)?)]);
+    }
+    let finish = end.format("%Y-%m-%d").to_string();
+    let mut passwords = Vec::new();
+    for (date, potd) in rspotd::generate_multiple(&begin, &finish, seed)? {
+        passwords.push((NaiveDate::parse_from_str(&date, "%Y-%m-%d")?, potd));
+    }
+    Ok(passwords)
+}
@@ -0,0 +1,39 @@
+/*
+ * C bindings for generating ARRIS/Commscope password-of-the-day, from the rspotd-ffi
+ * crate. Link against librspotd_ffi (the .so/.dylib/.dll, or the static library).
+ *
+ * Strings returned by rspotd_generate and rspotd_generate_range are allocated by the
+ * library and must be released with rspotd_free. They return NULL on failure, leaving
+ * the reason for rspotd_last_error.
+ */
+#ifndef RSPOTD_H
+#define RSPOTD_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The password for a date in YYYY-MM-DD form; seed may be NULL for the default seed. */
+char *rspotd_generate(const char *date, const char *seed);
+
+/*
+ * The passwords for every date from start to end, inclusive, as one
+ * "YYYY-MM-DD\tPASSWORD\n" line per date in date order; seed may be NULL for the
+ * default seed. The range may be any number of years long.
+ */
+char *rspotd_generate_range(const char *start, const char *end, const char *seed);
+
+/*
+ * The message of the last call on this thread that failed, or NULL if none has. Valid
+ * until the next failing call on the same thread; don't free it.
+ */
+const char *rspotd_last_error(void);
+
+/* Release a string returned by rspotd_generate or rspotd_generate_range; NULL is ignored. */
+void rspotd_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
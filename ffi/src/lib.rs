@@ -0,0 +1,145 @@
+//! C bindings for the password-of-the-day generator, declared in `include/rspotd.h`. Only the
+//! rspotd library and rspotd-core without its formats are used, so a program linking it
+//! doesn't need the command line at all.
+//!
+//! Strings handed back are allocated here and must be released with [`rspotd_free`]. A
+//! function that fails returns NULL and leaves its message for [`rspotd_last_error`].
+
+use chrono::NaiveDate;
+use rspotd::vals::DEFAULT_SEED;
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{self, UnwindSafe},
+    ptr,
+};
+
+thread_local! {
+    // the message of the last call on this thread that failed
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// The password for a date in YYYY-MM-DD form, with the default seed if `seed` is NULL.
+///
+/// # Safety
+///
+/// `date` must be a NUL-terminated string, and `seed` one as well or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn rspotd_generate(date: *const c_char, seed: *const c_char) -> *mut c_char {
+    hand_back(guarded(|| {
+        let date = argument(date, "date")?;
+        let seed = optional_argument(seed, "seed")?.unwrap_or(DEFAULT_SEED);
+        rspotd_core::validate_seed(seed)?;
+        parse_date(date)?;
+        rspotd::generate(date, seed).map_err(|err| err.to_string())
+    }))
+}
+
+/// The passwords for every date from `start` to `end`, inclusive, with the default seed if
+/// `seed` is NULL, as one `YYYY-MM-DD<TAB>PASSWORD` line per date in date order. The range
+/// may be any number of years long.
+///
+/// # Safety
+///
+/// `start` and `end` must be NUL-terminated strings, and `seed` one as well or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn rspotd_generate_range(
+    start: *const c_char,
+    end: *const c_char,
+    seed: *const c_char,
+) -> *mut c_char {
+    hand_back(guarded(|| {
+        let start = parse_date(argument(start, "start")?)?;
+        let end = parse_date(argument(end, "end")?)?;
+        let seed = optional_argument(seed, "seed")?.unwrap_or(DEFAULT_SEED);
+        rspotd_core::validate_seed(seed)?;
+        if end < start {
+            return Err(String::from(
+                "Invalid date range. Beginning date must not occur after end date.",
+            ));
+        }
+        let passwords =
+            rspotd_core::range::generate_range(start, end, seed).map_err(|err| err.to_string())?;
+        Ok(passwords
+            .iter()
+            .map(|(date, potd)| format!("{}\t{}\n", date.format("%Y-%m-%d"), potd))
+            .collect())
+    }))
+}
+
+/// The message of the last call on this thread that failed, or NULL if none has. It stays
+/// valid until the next failing call on the same thread, and mustn't be freed.
+#[no_mangle]
+pub extern "C" fn rspotd_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// Release a string returned by `rspotd_generate` or `rspotd_generate_range`; NULL is
+/// ignored.
+///
+/// # Safety
+///
+/// `string` must have been returned by one of those functions and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn rspotd_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+unsafe fn argument<'a>(value: *const c_char, name: &str) -> Result<&'a str, String> {
+    optional_argument(value, name)?.ok_or_else(|| format!("{} is NULL", name))
+}
+
+unsafe fn optional_argument<'a>(
+    value: *const c_char,
+    name: &str,
+) -> Result<Option<&'a str>, String> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map(Some)
+        .map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+// rspotd panics on a seed or date it can't use, so they're checked as the command line does
+// first
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Unable to parse date '{}', expected YYYY-MM-DD.", date))?;
+    rspotd_core::validate_date(parsed, date)?;
+    Ok(parsed)
+}
+
+// the result of `body`, or its panic as an error, since unwinding out of an extern "C"
+// function aborts the program that called it
+fn guarded(body: impl FnOnce() -> Result<String, String> + UnwindSafe) -> Result<String, String> {
+    panic::catch_unwind(body).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown error"));
+        Err(format!("rspotd failed unexpectedly: {}", message))
+    })
+}
+
+// the string for the caller to free, or NULL with the error kept for rspotd_last_error
+fn hand_back(result: Result<String, String>) -> *mut c_char {
+    match result.and_then(|value| CString::new(value).map_err(|err| err.to_string())) {
+        Ok(value) => value.into_raw(),
+        Err(err) => {
+            // messages come from Rust strings, which only contain NUL if given one
+            let err = CString::new(err.replace('\0', "")).unwrap_or_default();
+            LAST_ERROR.with(|error| *error.borrow_mut() = Some(err));
+            ptr::null_mut()
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::plugin::plugins;
+use chrono::{Datelike, NaiveDate};
+use rspotd::vals::{ALPHANUM, DEFAULT_SEED, TABLE1, TABLE2};
+use std::{
+    error::Error,
+    sync::{PoisonError, RwLock},
+};
+
+/// The algorithms built in; plugins add to them, see [`names`]
+pub const ALGORITHMS: [&str; 1] = ["arris"];
+
+// the algorithm chosen for this run, if not ARRIS
+static SELECTED: RwLock<Option<&'static dyn PotdAlgorithm>> = RwLock::new(None);
+
+/// A password-of-the-day scheme: how a seed and a date become the password
+pub trait PotdAlgorithm: Sync {
+    /// The name given to --algorithm
+    fn name(&self) -> &'static str;
+
+    /// The seed used when none is given
+    fn default_seed(&self) -> &'static str;
+
+    /// Explain exactly what is wrong with a seed the algorithm can't use and how to fix it
+    fn validate_seed(&self, seed: &str) -> Result<(), String>;
+
+    /// The password for a single date
+    fn generate(&self, date: NaiveDate, seed: &str) -> Result<String, Box<dyn Error>>;
+
+    /// The password for every date from start to end, inclusive; one date at a time unless
+    /// the algorithm has a faster way
+    fn generate_range(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        seed: &str,
+    ) -> Result<Vec<(NaiveDate, String)>, Box<dyn Error>> {
+        let mut passwords = Vec::new();
+        for date in start.iter_days().take_while(|date| *date <= end) {
+            passwords.push((date, self.generate(date, seed)?));
+        }
+        Ok(passwords)
+    }
+
+    /// The intermediate values the algorithm goes through for a date, as label and value
+    /// pairs in calculation order ending with the password, for checking it by hand
+    fn steps(
+        &self,
+        _date: NaiveDate,
+        _seed: &str,
+    ) -> Result<Vec<(&'static str, String)>, Box<dyn Error>> {
+        Err(format!("the {} algorithm's steps can't be shown", self.name()).into())
+    }
+}
+
+/// The algorithm with the given name, one of [`names`]; a built-in one wins over a plugin
+/// with the same name
+pub fn by_name(name: &str) -> Option<&'static dyn PotdAlgorithm> {
+    match name {
+        "arris" => Some(&Arris),
+        _ => plugins()
+            .iter()
+            .find(|plugin| plugin.name() == name)
+            .map(|plugin| plugin as &dyn PotdAlgorithm),
+    }
+}
+
+/// The names --algorithm accepts: the built-in algorithms, then the plugins
+pub fn names() -> Vec<&'static str> {
+    let mut names = ALGORITHMS.to_vec();
+    for plugin in plugins() {
+        if !names.contains(&plugin.name()) {
+            names.push(plugin.name());
+        }
+    }
+    names
+}
+
+/// Use the named algorithm for the rest of the run, which is ARRIS until this is called
+pub fn select(name: &str) -> Result<(), String> {
+    let algorithm = by_name(name).ok_or_else(|| {
+        format!(
+            "Unknown algorithm '{}', must be one of: {}.",
+            name,
+            names().join(", ")
+        )
+    })?;
+    *SELECTED.write().unwrap_or_else(PoisonError::into_inner) = Some(algorithm);
+    Ok(())
+}
+
+/// Go back to ARRIS, as at the start of a run
+pub fn reset() {
+    *SELECTED.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// The algorithm selected with --algorithm, or ARRIS
+pub fn selected() -> &'static dyn PotdAlgorithm {
+    SELECTED
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .unwrap_or(&Arris)
+}
+
+/// The ARRIS/CommScope password of the day, generated by the rspotd library
+pub struct Arris;
+
+impl PotdAlgorithm for Arris {
+    fn name(&self) -> &'static str {
+        "arris"
+    }
+
+    fn default_seed(&self) -> &'static str {
+        DEFAULT_SEED
+    }
+
+    fn validate_seed(&self, seed: &str) -> Result<(), String> {
+        rspotd_core::validate_seed(seed)
+    }
+
+    fn generate(&self, date: NaiveDate, seed: &str) -> Result<String, Box<dyn Error>> {
+        rspotd::generate(&date.format("%Y-%m-%d").to_string(), seed)
+    }
+
+    // rspotd_core generates the range a year at a time, which rspotd can do much faster than a
+    // date at a time, and works around its refusal of longer ranges
+    fn generate_range(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        seed: &str,
+    ) -> Result<Vec<(NaiveDate, String)>, Box<dyn Error>> {
+        rspotd_core::range::generate_range(start, end, seed)
+    }
+
+    fn steps(
+        &self,
+        date: NaiveDate,
+        seed: &str,
+    ) -> Result<Vec<(&'static str, String)>, Box<dyn Error>> {
+        let day = date.format("%Y-%m-%d").to_string();
+        // rejects what the generator would, so the steps below can't fail
+        rspotd::generate(&day, seed)?;
+        let padded = if seed == DEFAULT_SEED {
+            seed.to_string()
+        } else if seed.len() == 4 {
+            format!("{}{}{}", seed, seed, &seed[..2])
+        } else {
+            format!("{}{}", seed, &seed[..10 - seed.len()])
+        };
+        // only the year's digits after the first two count, e.g. 24 for 2024
+        let year: i32 = date.year().to_string()[2..].parse()?;
+        let month = date.month() as i32;
+        let day_of_month = date.day() as i32;
+        let weekday = date.weekday().num_days_from_monday() as usize;
+        let mut a: Vec<i32> = TABLE1[weekday].to_vec();
+        a.push(day_of_month);
+        a.push((year + month - day_of_month).rem_euclid(36));
+        a.push((((3 + (year + month) % 12) * day_of_month) % 37) % 36);
+        let b: Vec<i32> = padded.chars().map(|c| c as i32).collect();
+        let mut c: Vec<i32> = (0..8).map(|i| (a[i] + b[i]) % 36).collect();
+        c.push(c.iter().sum::<i32>() % 36);
+        c.push((c[8] % 6).pow(2));
+        let row = (c[8] % 6) as usize;
+        let d: Vec<i32> = TABLE2[row].iter().map(|&i| c[i as usize]).collect();
+        let indexes: Vec<i32> = (0..10).map(|i| (b[i] + d[i]) % 36).collect();
+        let password: String = indexes.iter().map(|&i| ALPHANUM[i as usize]).collect();
+        let list = |values: &[i32]| {
+            values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        Ok(vec![
+            ("padded seed", padded.clone()),
+            ("date values", list(&a)),
+            ("seed values", list(&b)),
+            ("combined", list(&c)),
+            ("shuffle row", row.to_string()),
+            ("shuffled", list(&d)),
+            ("indexes", list(&indexes)),
+            ("password", password),
+        ])
+    }
+}
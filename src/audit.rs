@@ -0,0 +1,146 @@
+use crate::seed::seed_fingerprint;
+use chrono::{NaiveDate, SecondsFormat, Utc};
+use std::{
+    env, process,
+    sync::{PoisonError, RwLock},
+};
+
+/// Where every disclosed password is recorded
+#[derive(Clone, Copy)]
+pub enum Sink {
+    /// RFC 5424 messages with structured data on the local syslog socket
+    Syslog,
+    /// The systemd journal's native protocol, with each field searchable
+    Journald,
+}
+
+pub const SINKS: [&str; 2] = ["syslog", "journald"];
+
+// facility 10 (authpriv), so the messages land in the access-restricted log on most systems
+const FACILITY: u8 = 10;
+const INFO: u8 = 6;
+
+static SINK: RwLock<Option<Sink>> = RwLock::new(None);
+
+/// Record every password disclosed from now on in the given sink, or stop recording them if
+/// none is given
+pub fn set_sink(sink: Option<&str>) {
+    *SINK.write().unwrap_or_else(PoisonError::into_inner) = sink.map(|sink| match sink {
+        "journald" => Sink::Journald,
+        _ => Sink::Syslog,
+    });
+}
+
+/// Log that a password was disclosed, if a sink is set. `channel` is how it was handed
+/// out (cli, watch, http, ...) and `client` who asked for it, when known. Callers should
+/// not disclose the password if this fails.
+pub fn record(
+    channel: &str,
+    client: Option<&str>,
+    seed: &str,
+    date: NaiveDate,
+    potd: &str,
+) -> Result<(), String> {
+    let Some(sink) = *SINK.read().unwrap_or_else(PoisonError::into_inner) else {
+        return Ok(());
+    };
+    let user = env::var("USER")
+        .or_else(|_| env::var("LOGNAME"))
+        .unwrap_or_else(|_| String::from("-"));
+    let date = date.format("%Y-%m-%d").to_string();
+    let seed_id = seed_fingerprint(seed);
+    let mut fields = vec![
+        ("date", date.as_str()),
+        ("password", potd),
+        ("seed_id", seed_id.as_str()),
+        ("channel", channel),
+        ("user", user.as_str()),
+    ];
+    if let Some(client) = client {
+        fields.push(("client", client));
+    }
+    let message = format!("Password of the day for {} disclosed via {}", date, channel);
+    match sink {
+        Sink::Syslog => send_syslog(&message, &fields),
+        Sink::Journald => send_journald(&message, &fields),
+    }
+}
+
+// <PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD-ELEMENT] MSG, leaving the hostname
+// for the syslog daemon to fill in
+fn send_syslog(message: &str, fields: &[(&str, &str)]) -> Result<(), String> {
+    let params: Vec<String> = fields
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, sd_escape(value)))
+        .collect();
+    let line = format!(
+        "<{}>1 {} - rspotd-cli {} disclosure [potd@32473 {}] {}",
+        FACILITY * 8 + INFO,
+        Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        process::id(),
+        params.join(" "),
+        message
+    );
+    send(&["/dev/log", "/var/run/syslog"], line.as_bytes())
+        .map_err(|err| format!("Unable to write to syslog: {}", err))
+}
+
+// KEY=value lines, with the structured fields named RSPOTD_DATE, RSPOTD_PASSWORD and so on
+fn send_journald(message: &str, fields: &[(&str, &str)]) -> Result<(), String> {
+    let mut datagram = Vec::new();
+    let facility = FACILITY.to_string();
+    let priority = INFO.to_string();
+    let mut entries = vec![
+        (String::from("MESSAGE"), message),
+        (String::from("PRIORITY"), priority.as_str()),
+        (String::from("SYSLOG_FACILITY"), facility.as_str()),
+        (String::from("SYSLOG_IDENTIFIER"), "rspotd-cli"),
+    ];
+    for (name, value) in fields {
+        entries.push((format!("RSPOTD_{}", name.to_uppercase()), value));
+    }
+    for (name, value) in entries {
+        datagram.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            // multi-line values are sent as a little-endian length followed by the raw bytes
+            datagram.push(b'\n');
+            datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            datagram.push(b'=');
+        }
+        datagram.extend_from_slice(value.as_bytes());
+        datagram.push(b'\n');
+    }
+    send(&["/run/systemd/journal/socket"], &datagram)
+        .map_err(|err| format!("Unable to write to the systemd journal: {}", err))
+}
+
+// param values escape ", \ and ]
+fn sd_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
+
+#[cfg(unix)]
+fn send(paths: &[&str], datagram: &[u8]) -> std::io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+    let socket = UnixDatagram::unbound()?;
+    let mut result = Err(std::io::Error::from(std::io::ErrorKind::NotFound));
+    for path in paths {
+        result = socket.send_to(datagram, path).map(|_| ());
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(not(unix))]
+fn send(_paths: &[&str], _datagram: &[u8]) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "only available on Unix",
+    ))
+}
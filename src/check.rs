@@ -0,0 +1,138 @@
+use crate::telnet::{Login, Telnet};
+use crate::webui::{self, WebLogin, WebOutcome};
+use std::time::{Duration, Instant};
+
+/// The methods `check` can log in with
+pub const METHODS: [&str; 2] = ["telnet", "http"];
+
+/// What to log in to and what counts as slow
+pub struct CheckOptions {
+    pub host: String,
+    /// One of [`METHODS`]
+    pub method: String,
+    /// The method's usual port if not given
+    pub port: Option<u16>,
+    /// root for telnet, or the web interface's usual user, if not given
+    pub user: Option<String>,
+    /// The web interface to log in to over http, one of [`webui::MODELS`]
+    pub model: String,
+    /// Seconds a login may take before it's a warning, or critical
+    pub warning: f64,
+    pub critical: f64,
+    /// Seconds to wait for the device at each step
+    pub timeout: Duration,
+}
+
+/// The result of a Nagios/Icinga plugin, which is also its exit status
+#[derive(Clone, Copy)]
+pub enum Status {
+    Ok = 0,
+    Warning = 1,
+    Critical = 2,
+    Unknown = 3,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::Warning => "WARNING",
+            Status::Critical => "CRITICAL",
+            Status::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+// how far a login got
+enum Outcome {
+    Accepted,
+    Rejected(String),
+    // the device didn't answer the way a login prompt should
+    Unexpected(String),
+}
+
+/// Log in to the device with the password and return the status along with the plugin's
+/// output line, e.g. `POTD OK - ... | time=0.412s;5;10;0`
+pub fn check(options: &CheckOptions, potd: &str) -> (Status, String) {
+    let started = Instant::now();
+    let web = WebLogin {
+        host: options.host.clone(),
+        model: options.model.clone(),
+        user: options.user.clone(),
+        port: options.port,
+        https: false,
+        timeout: options.timeout,
+    };
+    let (user, outcome) = match options.method.as_str() {
+        "http" => (web.user(), login_http(&web, potd)),
+        _ => {
+            let user = options.user.as_deref().unwrap_or("root");
+            (user, login_telnet(options, user, potd))
+        }
+    };
+    let elapsed = started.elapsed().as_secs_f64();
+    let target = format!("{}@{} over {}", user, options.host, options.method);
+    let (status, message) = match outcome {
+        Ok(Outcome::Accepted) if elapsed >= options.critical => (
+            Status::Critical,
+            format!(
+                "{} accepted today's password but took {:.2}s",
+                target, elapsed
+            ),
+        ),
+        Ok(Outcome::Accepted) if elapsed >= options.warning => (
+            Status::Warning,
+            format!(
+                "{} accepted today's password but took {:.2}s",
+                target, elapsed
+            ),
+        ),
+        Ok(Outcome::Accepted) => (
+            Status::Ok,
+            format!("{} accepted today's password in {:.2}s", target, elapsed),
+        ),
+        Ok(Outcome::Rejected(said)) if said.is_empty() => (
+            Status::Critical,
+            format!("{} rejected today's password", target),
+        ),
+        Ok(Outcome::Rejected(said)) => (
+            Status::Critical,
+            format!("{} rejected today's password: {}", target, said),
+        ),
+        Ok(Outcome::Unexpected(what)) => (Status::Unknown, format!("{}: {}", target, what)),
+        Err(err) => (
+            Status::Critical,
+            format!("Unable to log in to {}: {}", target, err),
+        ),
+    };
+    let line = format!(
+        "POTD {} - {} | time={:.3}s;{};{};0",
+        status.label(),
+        message,
+        elapsed,
+        options.warning,
+        options.critical
+    );
+    (status, line)
+}
+
+fn login_telnet(options: &CheckOptions, user: &str, potd: &str) -> Result<Outcome, String> {
+    let mut telnet = Telnet::connect(&options.host, options.port.unwrap_or(23), options.timeout)
+        .map_err(|err| err.to_string())?;
+    match telnet.login(user, potd, options.timeout) {
+        Ok(Login::Accepted) => Ok(Outcome::Accepted),
+        Ok(Login::Rejected(said)) => Ok(Outcome::Rejected(said)),
+        Ok(Login::Unanswered) => Ok(Outcome::Unexpected(String::from(
+            "no answer to the password",
+        ))),
+        Err(err) => Ok(Outcome::Unexpected(err.to_string())),
+    }
+}
+
+fn login_http(web: &WebLogin, potd: &str) -> Result<Outcome, String> {
+    Ok(match webui::login(web, potd)? {
+        WebOutcome::Accepted(_) => Outcome::Accepted,
+        WebOutcome::Rejected(said) => Outcome::Rejected(said),
+        WebOutcome::Unexpected(what) => Outcome::Unexpected(what),
+    })
+}
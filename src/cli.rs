@@ -0,0 +1,2585 @@
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike, Days, Locale, NaiveDate, Weekday,
+};
+use clap::{
+    builder::PossibleValuesParser,
+    parser::ValueSource,
+    error::ErrorKind,
+    ArgAction, ArgGroup, CommandFactory, FromArgMatches, Parser, Subcommand,
+};
+use chrono_tz::Tz;
+use clap_complete::Shell;
+use crate::config::{default_config_path, load_config, set_config_value, Config};
+use crate::dates::{
+    current_date, month_range, read_dates, resolve_date, set_input_format,
+    set_timezone, timezone_arg, today, try_parse_date, week_range, weekday_arg, year_range, DateFilter,
+};
+use crate::error::Error;
+use crate::output::{output_file, OutputFile, OutputOptions};
+use crate::algorithm::{self, PotdAlgorithm};
+use crate::{audit, format, potd, repl, seed, ssh, wizard};
+use potd::{generate_range, generate_range_with_progress};
+use format::{
+    binary_format, explain_date_format, format_des_table, format_document, format_fleet, format_potd, format_potd_range, format_qr_png,
+    locale_arg, output_path, per_date_output, validate_date_format, validate_template, FleetRow, FormatOptions, FLEET_FORMATS, FORMATS,
+};
+use format::{Entry, OutputWriter};
+use indicatif::{ProgressBar, ProgressStyle};
+use tracing_subscriber::EnvFilter;
+use crate::serve::{serve, ServeOptions};
+use crate::telnet::{Login, Telnet};
+use crate::watch::watch;
+use seed::{
+    forget_keyring_seed, keyring_seed, prompt_seed, random_seed, read_seed, read_seed_file,
+    read_seeds_file, seed_fingerprint, store_keyring_seed, validate_seed,
+};
+use potd::generate;
+use rspotd::seed_to_des;
+use std::{
+    collections::BTreeMap, ffi::OsString, fs, io::{self, IsTerminal, Read, Write}, path::{Path, PathBuf}, process::ExitCode,
+};
+#[cfg(feature = "check")]
+use crate::check;
+#[cfg(feature = "clipboard")]
+use crate::clipboard;
+#[cfg(feature = "db")]
+use crate::db;
+#[cfg(feature = "email")]
+use crate::email;
+#[cfg(feature = "webhook")]
+use format::format_json_payload;
+#[cfg(feature = "grpc")]
+use crate::grpc;
+#[cfg(feature = "mqtt")]
+use crate::mqtt;
+#[cfg(feature = "encrypt")]
+use crate::output;
+#[cfg(feature = "notify")]
+use crate::notify;
+#[cfg(feature = "tui")]
+use crate::tui;
+#[cfg(feature = "webhook")]
+use crate::webhook;
+#[cfg(feature = "login")]
+use crate::webui;
+
+#[derive(Parser)]
+#[clap(
+    author = "Shea Zerda",
+    version,
+    about = "ARRIS/Commscope password-of-the-day generator"
+)]
+#[command(version, about, long_about = None)]
+#[command(group(ArgGroup::new("span").args(["range", "days", "month", "year", "week"])))]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(
+        short = 's',
+        long = "seed",
+        global = true,
+        env = "RSPOTD_SEED",
+        hide_env_values = true,
+        action = ArgAction::Append,
+        help = "String of 4-8 characters, used in password generation to mutate output; use - to read it from stdin. May be given more than once"
+    )]
+    seed: Option<Vec<String>>,
+
+    #[arg(
+        short = 'P',
+        long = "seed-prompt",
+        global = true,
+        help = "Prompt for the seed without echoing it to the terminal"
+    )]
+    seed_prompt: bool,
+
+    #[arg(
+        long = "seed-file",
+        global = true,
+        value_name = "PATH",
+        conflicts_with = "seed_prompt",
+        help = "Read the seed from the first line of the given file"
+    )]
+    seed_file: Option<String>,
+
+    #[arg(
+        long = "seeds-file",
+        global = true,
+        value_name = "PATH",
+        conflicts_with_all = ["seed_prompt", "seed_file"],
+        help = "Generate for every seed listed in the given file, one per line"
+    )]
+    seeds_file: Option<String>,
+
+    #[arg(
+        short = 'c',
+        long = "config",
+        global = true,
+        help = "Read defaults from the given config file instead of the default location"
+    )]
+    config: Option<String>,
+
+    #[arg(
+        short = 'p',
+        long = "profile",
+        env = "RSPOTD_PROFILE",
+        global = true,
+        help = "Use the seed and settings of the named profile from the config file"
+    )]
+    profile: Option<String>,
+
+    #[arg(
+        long = "algorithm",
+        env = "RSPOTD_ALGORITHM",
+        global = true,
+        help = "Password-of-the-day scheme to generate with: arris, the default, or the name of a plugin"
+    )]
+    algorithm: Option<String>,
+
+    #[arg(
+        short = 'd',
+        long = "date",
+        conflicts_with = "range",
+        allow_hyphen_values = true,
+        action = ArgAction::Append,
+        value_delimiter = ',',
+        help = "Generate a password for the given date, in YYYY-MM-DD format or relative such as tomorrow, next monday or +3 days, or @<epoch seconds>. May be repeated or comma-separated"
+    )]
+    date: Option<Vec<String>>,
+
+    #[arg(
+        long = "dates-from",
+        value_name = "FILE",
+        conflicts_with_all = ["date", "range"],
+        help = "Generate a password for every date listed in the given file, one per line; use - for stdin"
+    )]
+    dates_from: Option<String>,
+
+    #[arg(
+        short = 'D',
+        long = "des",
+        conflicts_with_all = ["date", "range", "dates_from"],
+        num_args = 0,
+        help = "Output DES representation of seed"
+    )]
+    des: bool,
+
+    #[arg(
+        long = "all-profiles",
+        requires = "des",
+        help = "With --des, list the DES representation of every profile in the config file"
+    )]
+    all_profiles: bool,
+
+    #[arg(
+        long = "input-date-format",
+        value_name = "FORMAT",
+        global = true,
+        env = "RSPOTD_INPUT_DATE_FORMAT",
+        hide_env_values = true,
+        help = "Also accept dates given in this format, e.g. %m/%d/%Y or %d.%m.%Y; see date(1) for valid format syntax"
+    )]
+    input_date_format: Option<String>,
+
+    #[arg(
+        long = "timezone",
+        value_name = "ZONE",
+        global = true,
+        env = "RSPOTD_TIMEZONE",
+        value_parser = timezone_arg,
+        help = "Time zone that decides what today is, e.g. America/Chicago; the local time zone by default"
+    )]
+    timezone: Option<Tz>,
+
+    #[arg(
+        long = "utc",
+        global = true,
+        help = "Take today's date in UTC, overriding --timezone"
+    )]
+    utc: bool,
+
+    #[arg(
+        long = "log-sink",
+        global = true,
+        env = "RSPOTD_LOG_SINK",
+        value_parser = PossibleValuesParser::new(audit::SINKS),
+        help = "Record every password handed out, with its date, seed fingerprint and user, in the local syslog or systemd journal"
+    )]
+    log_sink: Option<String>,
+
+    #[arg(
+        long = "error-format",
+        global = true,
+        env = "RSPOTD_ERROR_FORMAT",
+        value_parser = PossibleValuesParser::new(["text", "json"]),
+        default_value = "text",
+        help = "How to report errors; json writes {\"error\": {\"kind\", \"message\", \"hint\"}} on a single line to stderr"
+    )]
+    error_format: String,
+
+    #[arg(
+        long = "log-level",
+        global = true,
+        env = "RSPOTD_LOG",
+        value_parser = log_filter_arg,
+        help = "Diagnostics to log on stderr: off, error, warn, info, debug or trace, or a filter such as rspotd_cli=debug. Defaults to info for serve, grpc, watch and publish, and warn otherwise"
+    )]
+    log_level: Option<String>,
+
+    #[arg(
+        short = 'f',
+        long = "format",
+        env = "RSPOTD_FORMAT",
+        hide_env_values = true,
+        value_parser = PossibleValuesParser::new(FORMATS),
+        help = "Password output format"
+    )]
+    format: Option<String>,
+
+    #[arg(
+        long = "header",
+        help = "Include a header row in csv and tsv output"
+    )]
+    header: bool,
+
+    #[arg(
+        long = "export",
+        help = "Print export lines for a shell to eval, e.g. eval \"$(rspotd-cli --export)\"; implies --format env"
+    )]
+    export: bool,
+
+    #[arg(
+        short = 'q',
+        long = "quiet",
+        visible_alias = "password-only",
+        help = "Print only the password(s), without dates or other formatting"
+    )]
+    quiet: bool,
+
+    #[arg(
+        long = "color",
+        value_parser = PossibleValuesParser::new(["auto", "always", "never"]),
+        default_value = "auto",
+        help = "Colorize text and table output; auto colors only when printing to a terminal and NO_COLOR is unset"
+    )]
+    color: String,
+
+    #[arg(
+        short = 't',
+        long = "template",
+        help = "Template for each line of text output, using {date}, {potd} and {seed_hash} placeholders"
+    )]
+    template: Option<String>,
+
+    #[arg(
+        long = "title",
+        help = "Title for html and ics output"
+    )]
+    title: Option<String>,
+
+    #[arg(
+        short = 'F',
+        long = "date-format",
+        global = true,
+        env = "RSPOTD_DATE_FORMAT",
+        hide_env_values = true,
+        help = "Format the date string; see date(1) for valid format syntax"
+    )]
+    date_format: Option<String>,
+
+    #[arg(
+        long = "explain-format",
+        help = "Show what each specifier of the date format means and preview it against today's date"
+    )]
+    explain_format: bool,
+
+    #[arg(
+        long = "locale",
+        env = "RSPOTD_LOCALE",
+        value_parser = locale_arg,
+        help = "Language for month and day names such as %A and %B in --date-format, e.g. de_DE"
+    )]
+    locale: Option<Locale>,
+
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Password or list will be written to given filename; an existing file is only overwritten with --force or --backup, or added to with --append. {date} in the name writes a file per date, e.g. potd/{date}.txt, and - writes to stdout"
+    )]
+    output: Option<String>,
+
+    #[arg(
+        long = "append",
+        requires = "output",
+        help = "Add to the end of the --output file instead of overwriting it, e.g. to keep a log of issued passwords; csv and tsv headers are only written to an empty file"
+    )]
+    append: bool,
+
+    #[arg(
+        long = "mkdir",
+        requires = "output",
+        help = "Create the directories leading up to the --output file if they don't exist"
+    )]
+    mkdir: bool,
+
+    #[arg(
+        long = "force",
+        requires = "output",
+        help = "Overwrite the --output file if it already exists"
+    )]
+    force: bool,
+
+    #[arg(
+        long = "mode",
+        value_name = "MODE",
+        value_parser = mode_arg,
+        requires = "output",
+        help = "Permissions of the --output file in octal, e.g. 640; new files are otherwise readable by their owner only (600), and replaced ones keep theirs"
+    )]
+    mode: Option<u32>,
+
+    #[cfg(feature = "compress")]
+    #[arg(
+        long = "compress",
+        value_parser = PossibleValuesParser::new(["gzip", "zstd", "none"]),
+        requires = "output",
+        help = "Compress the --output file; by default a .gz or .zst file name gets gzip or zstd"
+    )]
+    compress: Option<String>,
+
+    #[cfg(feature = "encrypt")]
+    #[arg(
+        long = "encrypt-to",
+        value_name = "RECIPIENT",
+        value_parser = recipient_arg,
+        requires = "output",
+        conflicts_with = "append",
+        help = "Encrypt the --output file with age to this public key (age1... or ssh-ed25519/ssh-rsa); repeat for several keyholders"
+    )]
+    encrypt_to: Vec<String>,
+
+    #[arg(
+        long = "backup",
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak",
+        value_parser = backup_suffix_arg,
+        requires = "output",
+        conflicts_with = "append",
+        help = "Keep the file --output replaces, renamed with SUFFIX added (.bak if not given); date(1) specifiers give a timestamped name, e.g. --backup=.%Y%m%d-%H%M%S"
+    )]
+    backup: Option<String>,
+
+    #[cfg(feature = "clipboard")]
+    #[arg(
+        long = "copy",
+        help = "Also copy the password(s) to the clipboard, leaving them out of clipboard manager history"
+    )]
+    copy: bool,
+
+    #[cfg(feature = "clipboard")]
+    #[arg(
+        long = "clear-after",
+        value_name = "SECONDS",
+        requires = "copy",
+        help = "Clear the clipboard after this many seconds, unless something else was copied meanwhile"
+    )]
+    clear_after: Option<u64>,
+
+    #[arg(
+        short = 'r',
+        long = "range",
+        conflicts_with = "date",
+        num_args = 1..=2,
+        value_names = ["START", "END"],
+        allow_hyphen_values = true,
+        help="Generate a list of passwords given start and end dates; relative dates are accepted. END defaults to today, and .. in place of either date means today"
+    )]
+    range: Option<Vec<String>>,
+
+    #[arg(
+        long = "days",
+        value_name = "N",
+        conflicts_with_all = ["range", "dates_from"],
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Generate passwords for N days starting at --date, or today if no date is given"
+    )]
+    days: Option<u32>,
+
+    #[arg(
+        long = "month",
+        value_name = "YYYY-MM",
+        conflicts_with_all = ["date", "dates_from", "range", "days"],
+        help = "Generate passwords for every day of the given calendar month"
+    )]
+    month: Option<String>,
+
+    #[arg(
+        long = "year",
+        value_name = "YYYY",
+        conflicts_with_all = ["date", "dates_from", "range", "days", "month"],
+        help = "Generate passwords for every day of the given calendar year"
+    )]
+    year: Option<String>,
+
+    #[arg(
+        long = "week",
+        value_name = "YYYY-Www",
+        conflicts_with_all = ["date", "dates_from", "range", "days", "month", "year"],
+        help = "Generate passwords for Monday through Sunday of the given ISO 8601 week, e.g. 2024-W27"
+    )]
+    week: Option<String>,
+
+    #[arg(
+        long = "step",
+        value_name = "N",
+        requires = "span",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Only output every Nth day of the range, counting from its first day"
+    )]
+    step: Option<u32>,
+
+    #[arg(
+        long = "weekdays",
+        value_name = "DAYS",
+        requires = "span",
+        value_delimiter = ',',
+        value_parser = weekday_arg,
+        help = "Only output the given days of the week in a range, e.g. mon,wed,fri"
+    )]
+    weekdays: Option<Vec<Weekday>>,
+
+    #[arg(
+        long = "weekends",
+        requires = "span",
+        conflicts_with_all = ["weekdays", "workdays"],
+        help = "Only output Saturdays and Sundays in a range"
+    )]
+    weekends: bool,
+
+    #[arg(
+        long = "workdays",
+        requires = "span",
+        conflicts_with = "weekdays",
+        help = "Only output Monday through Friday in a range"
+    )]
+    workdays: bool,
+
+    #[arg(
+        long = "exclude",
+        value_name = "DATE",
+        requires = "span",
+        allow_hyphen_values = true,
+        action = ArgAction::Append,
+        value_delimiter = ',',
+        help = "Leave the given date out of a range, e.g. a holiday. May be repeated or comma-separated"
+    )]
+    exclude: Option<Vec<String>>,
+
+    #[arg(
+        long = "exclude-from",
+        value_name = "FILE",
+        requires = "span",
+        help = "Leave every date listed in the given file, one per line, out of a range"
+    )]
+    exclude_from: Option<String>,
+
+    #[arg(
+        long = "reverse",
+        requires = "span",
+        help = "Output a range newest date first"
+    )]
+    reverse: bool,
+
+    #[arg(
+        long = "limit",
+        value_name = "N",
+        requires = "span",
+        value_parser = clap::value_parser!(usize),
+        help = "Output at most N dates of a range per seed"
+    )]
+    limit: Option<usize>,
+
+    #[cfg(feature = "webhook")]
+    #[arg(
+        long = "webhook",
+        value_name = "URL",
+        help = "POST the generated passwords as JSON to the given URL"
+    )]
+    webhook: Option<String>,
+
+    #[cfg(feature = "webhook")]
+    #[arg(
+        long = "webhook-secret",
+        env = "RSPOTD_WEBHOOK_SECRET",
+        hide_env_values = true,
+        requires = "webhook",
+        help = "Sign webhook payloads with HMAC-SHA256 using this key, in an X-Rspotd-Signature header"
+    )]
+    webhook_secret: Option<String>,
+
+    #[cfg(feature = "webhook")]
+    #[arg(
+        long = "webhook-retries",
+        value_name = "N",
+        default_value_t = 3,
+        help = "How many times to retry a webhook delivery that failed"
+    )]
+    webhook_retries: u32,
+
+    #[arg(
+        short = 'v',
+        long = "verbose",
+        action = ArgAction::Count,
+        help = "Print output to console when writing to file; -vv also shows where the seed came from and the settings in effect, and -vvv the steps of the calculation for each date"
+    )]
+    verbose: u8,
+
+    // whether --seed was taken from RSPOTD_SEED, for -vv
+    #[arg(skip)]
+    seed_from_env: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Print a shell completion script to stdout")]
+    Completions {
+        #[arg(value_enum, help = "Shell to generate completions for")]
+        shell: Shell,
+    },
+
+    #[command(hide = true, about = "Print a roff man page to stdout or write man pages to a directory")]
+    Man {
+        #[arg(
+            short = 'o',
+            long = "out-dir",
+            help = "Write a man page for each command into the given directory"
+        )]
+        out_dir: Option<String>,
+    },
+
+    #[command(about = "Find which date in a range produces the given password")]
+    Which {
+        #[arg(short = 'w', long = "password", help = "Password to look for")]
+        password: String,
+
+        #[arg(
+            short = 'r',
+            long = "range",
+            num_args = 2,
+            value_names = ["START", "END"],
+            required = true,
+            help = "Dates to search, inclusive"
+        )]
+        range: Vec<String>,
+    },
+
+    #[command(about = "Check a password against the one generated for a date; exits 0 on a match, 1 otherwise")]
+    Verify {
+        #[arg(short = 'd', long = "date", help = "Date the password is for, today if not given")]
+        date: Option<String>,
+
+        #[arg(short = 'w', long = "password", help = "Password to check")]
+        password: String,
+    },
+
+    #[cfg(feature = "check")]
+    #[command(about = "Log in to a device with today's password and report the result as a Nagios/Icinga plugin: exits 0 (OK), 1 (WARNING), 2 (CRITICAL) or 3 (UNKNOWN)")]
+    Check {
+        #[arg(short = 'H', long = "host", help = "Device to log in to")]
+        host: String,
+
+        #[arg(
+            long = "method",
+            value_parser = PossibleValuesParser::new(check::METHODS),
+            default_value = "telnet",
+            help = "Log in at the telnet prompt, or to the web interface with HTTP basic authentication"
+        )]
+        method: String,
+
+        #[arg(long = "port", help = "Port to connect to, 23 for telnet and 80 for http if not given")]
+        port: Option<u16>,
+
+        #[arg(long = "user", help = "User to log in as; root for telnet and the model's usual user for http if not given")]
+        user: Option<String>,
+
+        #[arg(
+            long = "model",
+            value_parser = PossibleValuesParser::new(webui::MODELS),
+            default_value = "basic",
+            help = "Web interface to log in to with --method http, see the login subcommand"
+        )]
+        model: String,
+
+        #[arg(
+            long = "warning",
+            value_name = "SECONDS",
+            default_value_t = 5.0,
+            help = "Warn if logging in takes at least this long"
+        )]
+        warning: f64,
+
+        #[arg(
+            long = "critical",
+            value_name = "SECONDS",
+            default_value_t = 10.0,
+            help = "Critical if logging in takes at least this long"
+        )]
+        critical: f64,
+
+        #[arg(
+            long = "timeout",
+            value_name = "SECONDS",
+            default_value_t = 15,
+            help = "Give up waiting for the device after this long"
+        )]
+        timeout: u64,
+    },
+
+    #[cfg(feature = "login")]
+    #[command(about = "Log in to a device's web interface with today's password and report whether it worked")]
+    Login {
+        #[arg(help = "Device to log in to")]
+        host: String,
+
+        #[arg(
+            long = "model",
+            value_parser = PossibleValuesParser::new(webui::MODELS),
+            default_value = "basic",
+            help = "Login flow: basic for HTTP basic authentication, surfboard for SB8200-style modems, touchstone for Touchstone gateways"
+        )]
+        model: String,
+
+        #[arg(short = 'l', long = "user", help = "User to log in as; root for basic and admin otherwise if not given")]
+        user: Option<String>,
+
+        #[arg(long = "port", help = "Port to connect to, instead of 80 for http or 443 for https")]
+        port: Option<u16>,
+
+        #[arg(long = "https", help = "Connect over HTTPS; always the case for surfboard")]
+        https: bool,
+
+        #[arg(
+            long = "print-cookie",
+            help = "Print only the session cookie, as name=value pairs for a Cookie header, e.g. for curl -b"
+        )]
+        print_cookie: bool,
+
+        #[arg(
+            long = "timeout",
+            value_name = "SECONDS",
+            default_value_t = 15,
+            help = "Give up waiting for the device after this long"
+        )]
+        timeout: u64,
+    },
+
+    #[command(about = "Log in to a device over ssh with today's password, opening a shell or running the given command")]
+    Ssh {
+        #[arg(help = "Device to log in to, as HOST or USER@HOST")]
+        host: String,
+
+        #[arg(short = 'l', long = "user", help = "User to log in as, instead of ssh's default")]
+        user: Option<String>,
+
+        #[arg(long = "port", help = "Port to connect to, instead of ssh's default")]
+        port: Option<u16>,
+
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "COMMAND",
+            help = "Command to run on the device instead of a shell"
+        )]
+        command: Vec<String>,
+    },
+
+    #[command(about = "Log in to a device over telnet with today's password, then hand the session over")]
+    Telnet {
+        #[arg(help = "Device to log in to")]
+        host: String,
+
+        #[arg(long = "port", default_value_t = 23, help = "Port to connect to")]
+        port: u16,
+
+        #[arg(short = 'l', long = "user", default_value = "root", help = "User to log in as, if the device asks for one")]
+        user: String,
+
+        #[arg(
+            long = "timeout",
+            value_name = "SECONDS",
+            default_value_t = 15,
+            help = "Give up waiting for the login and password prompts after this long"
+        )]
+        timeout: u64,
+    },
+
+    #[command(about = "Act as a Terraform external data source: read a JSON query with an optional date and seed on stdin and write the password as JSON")]
+    External,
+
+    #[command(about = "Keep running and output the new password every time the date rolls over")]
+    Watch,
+
+    #[cfg(feature = "tui")]
+    #[command(about = "Browse passwords on a calendar in an interactive terminal interface")]
+    Tui,
+
+    #[command(about = "Read dates and commands such as 'range START END' interactively and print their passwords")]
+    Repl,
+
+    #[command(about = "Serve passwords as JSON over HTTP")]
+    Serve {
+        #[arg(
+            short = 'l',
+            long = "listen",
+            default_value = "127.0.0.1:8080",
+            help = "Address and port to listen on"
+        )]
+        listen: String,
+
+        #[arg(
+            long = "auth-token",
+            env = "RSPOTD_AUTH_TOKEN",
+            hide_env_values = true,
+            help = "Require this token in an Authorization: Bearer header on every request"
+        )]
+        auth_token: Option<String>,
+
+        #[arg(
+            long = "rate-limit",
+            value_name = "N",
+            default_value_t = 60,
+            help = "Requests allowed per client address per minute; 0 for no limit"
+        )]
+        rate_limit: u32,
+    },
+
+    #[cfg(feature = "grpc")]
+    #[command(about = "Serve passwords over gRPC, as described in proto/rspotd.proto")]
+    Grpc {
+        #[arg(
+            short = 'l',
+            long = "listen",
+            default_value = "127.0.0.1:50051",
+            help = "Address and port to listen on"
+        )]
+        listen: String,
+
+        #[arg(
+            long = "auth-token",
+            env = "RSPOTD_AUTH_TOKEN",
+            hide_env_values = true,
+            help = "Require this token in authorization: Bearer metadata on every request"
+        )]
+        auth_token: Option<String>,
+    },
+
+    #[cfg(feature = "mqtt")]
+    #[command(about = "Publish the password to a message broker every time the date rolls over")]
+    Publish {
+        #[command(subcommand)]
+        target: PublishTarget,
+    },
+
+    #[cfg(feature = "email")]
+    #[command(about = "Send the generated passwords somewhere instead of printing them")]
+    Send {
+        #[command(subcommand)]
+        target: SendTarget,
+    },
+
+    #[cfg(feature = "notify")]
+    #[command(about = "Post the generated passwords to a chat channel")]
+    Notify {
+        #[command(subcommand)]
+        service: NotifyService,
+    },
+
+    #[cfg(feature = "db")]
+    #[command(about = "Store the generated passwords in a SQLite database, or look them up there")]
+    Db {
+        #[arg(long = "file", value_name = "FILE", help = "SQLite database file, created if it doesn't exist")]
+        file: String,
+
+        #[command(subcommand)]
+        action: Option<DbCommand>,
+    },
+
+    #[command(about = "Manage the seed stored in the OS keyring")]
+    Seed {
+        #[command(subcommand)]
+        action: SeedCommand,
+    },
+
+    #[command(about = "Print one date's password for the top-level seed, every profile and every device in the config file, as a single table or document")]
+    Fleet,
+
+    #[command(about = "Work with the devices in the [devices] table of the config file")]
+    Device {
+        #[command(subcommand)]
+        action: DeviceCommand,
+    },
+}
+
+#[cfg(feature = "mqtt")]
+#[derive(Subcommand)]
+enum PublishTarget {
+    #[command(about = "Publish to an MQTT broker, formatted with --format")]
+    Mqtt {
+        #[command(flatten)]
+        settings: mqtt::MqttSettings,
+
+        #[arg(long = "once", help = "Publish today's password and exit instead of waiting for the next day")]
+        once: bool,
+    },
+}
+
+#[cfg(feature = "email")]
+#[derive(Subcommand)]
+enum SendTarget {
+    #[command(about = "Mail the passwords using the [email] settings from the config file")]
+    Email {
+        #[arg(
+            long = "to",
+            value_name = "ADDRESS",
+            action = ArgAction::Append,
+            value_delimiter = ',',
+            help = "Recipient, instead of the ones in the config file. May be repeated or comma-separated"
+        )]
+        to: Vec<String>,
+
+        #[arg(long = "subject", help = "Subject line, instead of the one in the config file")]
+        subject: Option<String>,
+    },
+}
+
+#[cfg(feature = "notify")]
+#[derive(Subcommand)]
+enum NotifyService {
+    #[command(about = "Post to a Slack incoming webhook")]
+    Slack {
+        #[arg(
+            long = "webhook-url",
+            env = "RSPOTD_SLACK_WEBHOOK",
+            hide_env_values = true,
+            help = "Incoming webhook URL of the channel"
+        )]
+        url: String,
+    },
+
+    #[command(about = "Post to a Microsoft Teams channel workflow or incoming webhook")]
+    Teams {
+        #[arg(
+            long = "webhook-url",
+            env = "RSPOTD_TEAMS_WEBHOOK",
+            hide_env_values = true,
+            help = "Webhook URL of the channel"
+        )]
+        url: String,
+    },
+
+    #[command(about = "Send a Telegram message from a bot")]
+    Telegram {
+        #[arg(
+            long = "bot-token",
+            env = "RSPOTD_TELEGRAM_TOKEN",
+            hide_env_values = true,
+            help = "Token of the bot, as given by @BotFather"
+        )]
+        token: String,
+
+        #[arg(long = "chat-id", help = "Chat, group or @channel to send the message to")]
+        chat_id: String,
+
+        #[arg(
+            long = "api-url",
+            default_value = "https://api.telegram.org",
+            help = "Bot API server, for self-hosted ones"
+        )]
+        api_url: String,
+    },
+}
+
+#[cfg(feature = "db")]
+#[derive(Subcommand)]
+enum DbCommand {
+    #[command(about = "Print the stored passwords for a date as date, seed fingerprint and password; exits 1 if there are none")]
+    Query {
+        #[arg(short = 'd', long = "date", help = "Date to look up, today if not given")]
+        date: Option<String>,
+
+        #[arg(long = "seed-id", help = "Only the password for the seed with this fingerprint, as shown by -vv")]
+        seed_id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SeedCommand {
+    #[command(about = "Save a seed in the OS keyring; it is prompted for, or read from stdin when piped")]
+    Store,
+
+    #[command(about = "Use the stored seed automatically when no other seed is given")]
+    Use {
+        #[arg(long = "off", help = "Stop using the stored seed automatically")]
+        off: bool,
+    },
+
+    #[command(about = "Remove the seed from the OS keyring")]
+    Forget,
+
+    #[command(about = "Generate a cryptographically random seed")]
+    New {
+        #[arg(
+            short = 'l',
+            long = "length",
+            default_value_t = 8,
+            value_parser = clap::value_parser!(u8).range(4..=8),
+            help = "Number of characters in the seed"
+        )]
+        length: u8,
+
+        #[arg(
+            long = "save",
+            help = "Write the seed to the config file, in the --profile table if one is given"
+        )]
+        save: bool,
+
+        #[arg(long = "store", help = "Save the seed in the OS keyring")]
+        store: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum DeviceCommand {
+    #[command(about = "List the configured devices with their host, seed fingerprint and notes")]
+    List,
+
+    #[command(about = "Generate passwords with the device's seed; options such as --date and --format go before 'device'")]
+    Potd {
+        #[arg(help = "Name of the device in the config file")]
+        name: String,
+    },
+}
+
+// output written to a file is never colored, whatever --color says
+fn use_color(color: &str, to_file: bool) -> bool {
+    use std::io::IsTerminal;
+    if to_file {
+        return false;
+    }
+    match color {
+        "always" => true,
+        "never" => false,
+        _ => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            !no_color && std::io::stdout().is_terminal()
+        }
+    }
+}
+
+// "Password of the day for 2024-07-01", or "Passwords of the day 2024-07-01 to 2024-07-07"
+#[cfg(any(feature = "email", feature = "notify"))]
+fn default_subject(entries: &[Entry]) -> String {
+    let first = entries.iter().map(|entry| entry.date).min();
+    let last = entries.iter().map(|entry| entry.date).max();
+    match (first, last) {
+        (Some(first), Some(last)) if first != last => {
+            format!("Passwords of the day {} to {}", first, last)
+        }
+        (Some(first), _) => format!("Password of the day for {}", first),
+        _ => String::from("Password of the day"),
+    }
+}
+
+// whether the subcommand delivers the output somewhere other than stdout, where terminal
+// colors make no sense
+fn sends_elsewhere(command: &Option<Command>) -> bool {
+    match command {
+        #[cfg(feature = "email")]
+        Some(Command::Send { .. }) => true,
+        #[cfg(feature = "mqtt")]
+        Some(Command::Publish { .. }) => true,
+        #[cfg(feature = "notify")]
+        Some(Command::Notify { .. }) => true,
+        #[cfg(feature = "db")]
+        Some(Command::Db { .. }) => true,
+        _ => false,
+    }
+}
+
+// servers and watchers run unattended, so they log what they're doing unless told otherwise
+fn long_running(command: &Option<Command>) -> bool {
+    match command {
+        Some(Command::Serve { .. } | Command::Watch) => true,
+        #[cfg(feature = "grpc")]
+        Some(Command::Grpc { .. }) => true,
+        #[cfg(feature = "mqtt")]
+        Some(Command::Publish { .. }) => true,
+        _ => false,
+    }
+}
+
+fn log_filter_arg(filter: &str) -> Result<String, String> {
+    EnvFilter::try_new(filter)
+        .map(|_| filter.to_string())
+        .map_err(|err| format!("invalid log level or filter: {}", err))
+}
+
+fn backup_suffix_arg(suffix: &str) -> Result<String, String> {
+    if suffix.is_empty() || suffix.contains(std::path::is_separator) {
+        return Err(String::from(
+            "the suffix must be non-empty and can't contain a path separator",
+        ));
+    }
+    if StrftimeItems::new(suffix).any(|item| item == Item::Error) {
+        return Err(format!("'{}' has a date specifier that isn't valid", suffix));
+    }
+    Ok(suffix.to_string())
+}
+
+#[cfg(feature = "encrypt")]
+fn recipient_arg(recipient: &str) -> Result<String, String> {
+    output::recipient_key(recipient).map(|_| recipient.trim().to_string())
+}
+
+fn mode_arg(mode: &str) -> Result<u32, String> {
+    u32::from_str_radix(mode, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o7777)
+        .ok_or_else(|| String::from("expected an octal mode such as 600 or 0640"))
+}
+
+// timestamped, leveled logs on stderr; a second run in the same process keeps the first one's
+fn init_logging(filter: Option<&str>, command: &Option<Command>) {
+    let default = if long_running(command) { "info" } else { "warn" };
+    let filter = EnvFilter::try_new(filter.unwrap_or(default)).unwrap_or_default();
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .with_target(false)
+        .try_init();
+}
+
+// {"error": {"kind": ..., "message": ..., "hint": ...}} on a single line
+fn error_json(kind: &str, message: &str, hint: Option<&str>) -> String {
+    serde_json::json!({
+        "error": {
+            "kind": kind,
+            "message": message,
+            "hint": hint,
+        }
+    })
+    .to_string()
+}
+
+// whether errors should be reported as JSON, before the arguments are known to parse
+fn wants_json_errors(args: &[OsString]) -> bool {
+    let mut format = std::env::var_os("RSPOTD_ERROR_FORMAT");
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--error-format" {
+            format = args.next().cloned();
+        } else if let Some(value) = arg.to_str().and_then(|arg| arg.strip_prefix("--error-format=")) {
+            format = Some(OsString::from(value));
+        }
+    }
+    format.is_some_and(|format| format == "json")
+}
+
+// writing to stdout failed
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        write_failed(None, err)
+    }
+}
+
+// record the passwords with --log-sink before they are handed out, refusing to hand them
+// out if they can't be
+fn audit_entries(channel: &str, entries: &[Entry]) -> Result<(), Error> {
+    for entry in entries {
+        audit::record(channel, None, &entry.seed, entry.date, &entry.potd)
+            .map_err(Error::Delivery)?;
+    }
+    Ok(())
+}
+
+// with -vvv, the intermediate values the algorithm went through for each password
+fn print_steps(err: &mut dyn Write, entries: &[Entry]) -> Result<(), Error> {
+    for entry in entries {
+        let _ = writeln!(err, "Steps for {} (seed {}):", entry.date.format("%Y-%m-%d"), seed_fingerprint(&entry.seed));
+        for (label, value) in potd::steps(entry.date, &entry.seed).map_err(|err| err.to_string())? {
+            let _ = writeln!(err, "  {:<12} {}", label, value);
+        }
+    }
+    Ok(())
+}
+
+// a progress bar on stderr for ranges long enough to take a while, hidden when stderr isn't
+// a terminal
+fn range_progress(begin: NaiveDate, end: NaiveDate, seeds: usize) -> ProgressBar {
+    let days = (end - begin).num_days() as u64 + 1;
+    if days <= 366 {
+        return ProgressBar::hidden();
+    }
+    let progress = ProgressBar::new(days * seeds as u64);
+    progress.set_style(
+        ProgressStyle::with_template("{bar:40} {pos}/{len} dates, {eta} left")
+            .unwrap(),
+    );
+    progress
+}
+
+// dates generated at a time for a range, enough to keep every core busy without holding
+// more than a few years of passwords in memory
+const RANGE_BATCH_DAYS: u64 = 366 * 8;
+
+// every seed's passwords for the range in output order, handed to `each` a batch at a time
+// so that long ranges never have to be held in memory all at once
+fn range_batches(
+    seeds: &[String],
+    (begin, end): (NaiveDate, NaiveDate),
+    reverse: bool,
+    filter: &DateFilter,
+    limit: Option<usize>,
+    progress: &ProgressBar,
+    each: &mut dyn FnMut(Vec<Entry>) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut spans = Vec::new();
+    let mut start = Some(begin);
+    while let Some(first) = start.filter(|first| *first <= end) {
+        let last = end.min(first + Days::new(RANGE_BATCH_DAYS - 1));
+        spans.push((first, last));
+        start = last.succ_opt();
+    }
+    if reverse {
+        spans.reverse();
+    }
+    for seed in seeds {
+        let mut remaining = limit.unwrap_or(usize::MAX);
+        for &(first, last) in &spans {
+            if remaining == 0 {
+                break;
+            }
+            let mut passwords =
+                generate_range_with_progress(first, last, seed, |count| progress.inc(count))
+                    .map_err(|err| err.to_string())?;
+            if reverse {
+                passwords.reverse();
+            }
+            let batch: Vec<Entry> = passwords
+                .into_iter()
+                .filter(|(date, _)| filter.keep(begin, *date))
+                .take(remaining)
+                .map(|(date, potd)| Entry {
+                    seed: seed.to_string(),
+                    date,
+                    potd,
+                })
+                .collect();
+            remaining -= batch.len();
+            each(batch)?;
+        }
+    }
+    Ok(())
+}
+
+// the password for a single date for every seed
+fn day_entries(seeds: &[String], date: NaiveDate) -> Result<Vec<Entry>, String> {
+    let day = date.format("%Y-%m-%d").to_string();
+    seeds
+        .iter()
+        .map(|seed| {
+            Ok(Entry {
+                seed: seed.to_string(),
+                date,
+                potd: generate(&day, seed).map_err(|err| err.to_string())?,
+            })
+        })
+        .collect()
+}
+
+// the --output file, or with a {date} placeholder in it, a file for each date holding that
+// date's passwords for every seed
+fn save_output(
+    options: &FormatOptions,
+    entries: &[Entry],
+    potd: &str,
+    output: &str,
+    file_options: &OutputOptions,
+) -> Result<(), Error> {
+    if !per_date_output(output) {
+        return write_output(options, entries, potd, &output_file(output), file_options);
+    }
+    // the layout of directories is part of the name
+    let file_options = OutputOptions {
+        mkdir: true,
+        ..file_options.clone()
+    };
+    let mut dates: BTreeMap<NaiveDate, Vec<Entry>> = BTreeMap::new();
+    for entry in entries {
+        dates.entry(entry.date).or_default().push(entry.clone());
+    }
+    for (date, entries) in dates {
+        let output = output_path(output, date).map_err(Error::Usage)?;
+        let options = FormatOptions {
+            header: options.header && !appends_to_data(file_options.append, Some(&output)),
+            ..options.clone()
+        };
+        let potd = render(&options, &entries, false)?;
+        write_output(&options, &entries, &potd, &output_file(&output), &file_options)?;
+    }
+    Ok(())
+}
+
+// --format qr written to a .png file is drawn as an image, binary formats such as xlsx are
+// rendered whole, and anything else is written as text
+fn write_output(
+    options: &FormatOptions,
+    entries: &[Entry],
+    potd: &str,
+    path: &Path,
+    file_options: &OutputOptions,
+) -> Result<(), Error> {
+    if binary_format(&options.format) {
+        if file_options.append {
+            return Err(Error::Usage(format!(
+                "--format {} output can't be appended to; leave out --append.",
+                options.format
+            )));
+        }
+        return write_bytes(&format_document(options, entries)?, path, file_options);
+    }
+    if !writes_png(options, path) {
+        return write_to_file(potd, path, file_options);
+    }
+    if file_options.append {
+        return Err(Error::Usage(String::from(
+            "A PNG file can't be appended to; leave out --append or use a text file.",
+        )));
+    }
+    if entries.len() != 1 {
+        return Err(Error::Usage(String::from(
+            "A PNG file holds a single QR code; generate one date for one seed, or use a text file.",
+        )));
+    }
+    write_bytes(&format_qr_png(options, &entries[0])?, path, file_options)
+}
+
+fn write_bytes(bytes: &[u8], path: &Path, options: &OutputOptions) -> Result<(), Error> {
+    let mut file = OutputFile::create(path, options)?;
+    file.write_all(bytes)
+        .and_then(|_| file.commit())
+        .map_err(|err| write_failed(Some(path), err))
+}
+
+// the passwords as text, or nothing for a binary format, which is rendered when it's written
+fn render(options: &FormatOptions, entries: &[Entry], range: bool) -> Result<String, String> {
+    match range {
+        _ if binary_format(&options.format) => Ok(String::new()),
+        true => format_potd_range(options, entries),
+        false => format_potd(options, entries),
+    }
+}
+
+// whether --append adds to a file that already has something in it
+fn appends_to_data(append: bool, output: Option<&str>) -> bool {
+    append
+        && output
+            .and_then(|output| fs::metadata(output_file(output)).ok())
+            .is_some_and(|metadata| metadata.len() > 0)
+}
+
+fn writes_png(options: &FormatOptions, path: &Path) -> bool {
+    options.format == "qr"
+        && path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+}
+
+fn write_to_file(potd: &str, path: &Path, options: &OutputOptions) -> Result<(), Error> {
+    let mut file = OutputFile::create(path, options)?;
+    writeln!(file, "{}", potd)
+        .and_then(|_| file.commit())
+        .map_err(|err| write_failed(Some(path), err))
+}
+
+// a reader that stops early, e.g. `| head`, closes stdout; that ends the output quietly
+fn write_failed(path: Option<&Path>, err: io::Error) -> Error {
+    match path {
+        _ if err.kind() == io::ErrorKind::BrokenPipe => Error::ClosedPipe,
+        // an entry that couldn't be rendered, and the error says why
+        _ if err.kind() == io::ErrorKind::InvalidData => Error::Failed(err.to_string()),
+        Some(path) => Error::Io(format!("Unable to write to file '{}': {}", path.display(), err)),
+        None => Error::Io(format!("Unable to write output: {}", err)),
+    }
+}
+
+// output on its way to a file, also printed with --verbose
+struct Echo<'a, W: Write> {
+    inner: W,
+    echo: Option<&'a mut dyn Write>,
+}
+
+impl<W: Write> Write for Echo<'_, W> {
+    // the file is what was asked for, so it's finished even if stdout goes away
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write_all(buf)?;
+        if let Some(echo) = &mut self.echo {
+            let _ = echo.write_all(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(echo) = &mut self.echo {
+            let _ = echo.flush();
+        }
+        self.inner.flush()
+    }
+}
+
+fn write_man_pages(out_dir: Option<&str>, out: &mut dyn Write) -> Result<(), String> {
+    let command = Args::command();
+    let result = match out_dir {
+        Some(dir) => clap_mangen::generate_to(command, dir),
+        None => clap_mangen::Man::new(command).render(out),
+    };
+    result.map_err(|err| format!("Unable to write man page: {}", err))
+}
+
+// DES values for the top-level seed and every profile, for provisioning ACS server entries
+fn print_profile_des(config: &Config, format: &str, out: &mut dyn Write) -> Result<(), Error> {
+    let mut rows: Vec<(String, String)> = Vec::new();
+    if let Some(seed) = &config.seed {
+        rows.push((String::from("(default)"), seed.to_string()));
+    }
+    for (name, profile) in &config.profiles {
+        match profile.seed.as_ref().or(config.seed.as_ref()) {
+            Some(seed) => rows.push((name.to_string(), seed.to_string())),
+            None => {
+                return Err(Error::Failed(format!(
+                    "Profile '{}' has no seed and there is no default seed to fall back to.",
+                    name
+                )));
+            }
+        }
+    }
+    if rows.is_empty() {
+        return Err(Error::Failed(String::from("No seed or profiles are configured.")));
+    }
+    for (name, seed) in rows.iter_mut() {
+        if let Err(err) = validate_seed(seed) {
+            return Err(Error::InvalidSeed(format!("Profile '{}': {}", name, err)));
+        }
+        *seed = seed_to_des(seed).unwrap();
+    }
+    writeln!(out, "{}", format_des_table(format, &rows))?;
+    Ok(())
+}
+
+// today's password for the top-level seed, then each profile and device, in the order
+// `--all-profiles` and `device list` show them; seeds fall back as they do for a plain run
+fn fleet_rows(config: &Config, algorithm: Option<&str>, date: NaiveDate) -> Result<Vec<FleetRow>, Error> {
+    if config.profiles.is_empty() && config.devices.is_empty() {
+        return Err(Error::Failed(String::from("No profiles or devices are configured.")));
+    }
+    // asked for once, and only if something uses it
+    let mut keyring: Option<String> = None;
+    let mut fallback_seed = |algorithm: &dyn PotdAlgorithm| -> Result<String, Error> {
+        match &config.seed {
+            Some(seed) => Ok(seed.clone()),
+            None if config.keyring => {
+                if keyring.is_none() {
+                    keyring = Some(keyring_seed()?);
+                }
+                Ok(keyring.clone().unwrap())
+            }
+            None => Ok(algorithm.default_seed().to_string()),
+        }
+    };
+    // --algorithm wins over the config file, as it does for a plain run
+    let pick = |configured: Option<&String>| -> Result<&dyn PotdAlgorithm, Error> {
+        let name = algorithm.or(configured.map(|name| name.as_str())).unwrap_or("arris");
+        algorithm::by_name(name).ok_or_else(|| {
+            Error::Usage(format!("Unknown algorithm '{}', must be one of: {}.", name, algorithm::names().join(", ")))
+        })
+    };
+    // name, kind, host, configured seed and algorithm of each row
+    type Source<'a> = (String, &'static str, Option<String>, Option<&'a String>, &'static dyn PotdAlgorithm);
+    let mut seeds: Vec<Source> = Vec::new();
+    if config.seed.is_some() || config.keyring {
+        seeds.push((String::from("(default)"), "default", None, None, pick(config.algorithm.as_ref())?));
+    }
+    for (name, profile) in &config.profiles {
+        let algorithm = pick(profile.algorithm.as_ref().or(config.algorithm.as_ref()))?;
+        seeds.push((name.to_string(), "profile", None, profile.seed.as_ref(), algorithm));
+    }
+    for (name, device) in &config.devices {
+        let algorithm = pick(config.device_algorithm(name)?)?;
+        seeds.push((name.to_string(), "device", device.host.clone(), config.device_seed(name)?, algorithm));
+    }
+    let mut rows = Vec::with_capacity(seeds.len());
+    for (name, kind, host, seed, algorithm) in seeds {
+        let seed = match seed {
+            Some(seed) => seed.clone(),
+            None => fallback_seed(algorithm)?,
+        };
+        algorithm
+            .validate_seed(&seed)
+            .map_err(|err| Error::InvalidSeed(format!("'{}': {}", name, err)))?;
+        let potd = algorithm.generate(date, &seed).map_err(|err| err.to_string())?;
+        audit::record("fleet", None, &seed, date, &potd).map_err(Error::Delivery)?;
+        rows.push(FleetRow {
+            name,
+            kind,
+            host,
+            seed_id: seed_fingerprint(&seed),
+            potd,
+        });
+    }
+    Ok(rows)
+}
+
+// one device per line, its seed only by fingerprint so the list can be shared
+fn print_devices(config: &Config, out: &mut dyn Write) -> Result<(), Error> {
+    if config.devices.is_empty() {
+        return Err(Error::Failed(String::from("No devices are configured.")));
+    }
+    let mut rows = vec![[
+        String::from("NAME"),
+        String::from("HOST"),
+        String::from("SEED"),
+        String::from("NOTES"),
+    ]];
+    for (name, device) in &config.devices {
+        let algorithm = config.device_algorithm(name)?.map_or("arris", |name| name.as_str());
+        let algorithm = algorithm::by_name(algorithm).unwrap();
+        let seed = match config.device_seed(name)? {
+            Some(seed) => {
+                algorithm
+                    .validate_seed(seed)
+                    .map_err(|err| Error::InvalidSeed(format!("Device '{}': {}", name, err)))?;
+                seed_fingerprint(seed)
+            }
+            None if config.keyring => String::from("(keyring)"),
+            None => seed_fingerprint(algorithm.default_seed()),
+        };
+        rows.push([
+            name.to_string(),
+            device.host.clone().unwrap_or_default(),
+            seed,
+            device.notes.clone().unwrap_or_default(),
+        ]);
+    }
+    let widths: Vec<usize> = (0..3)
+        .map(|column| rows.iter().map(|row| row[column].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in &rows {
+        let line = format!(
+            "{:<name$}  {:<host$}  {:<seed$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            name = widths[0],
+            host = widths[1],
+            seed = widths[2]
+        );
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+// seeds given on the command line (or its environment) win over the config file and keyring;
+// reading them interactively keeps them out of shell history and ps output
+fn resolve_seeds(args: &Args, config: &Config) -> Result<Vec<String>, Error> {
+    let mut seeds: Vec<String> = Vec::new();
+    if args.seed_prompt {
+        seeds.push(prompt_seed().map_err(Error::Io)?);
+    } else if let Some(path) = &args.seed_file {
+        seeds.push(read_seed_file(path).map_err(Error::Io)?);
+    } else {
+        for seed in args.seed.iter().flatten() {
+            if seed == "-" {
+                seeds.push(read_seed().map_err(Error::Io)?);
+            } else {
+                seeds.push(seed.to_string());
+            }
+        }
+        if let Some(path) = &args.seeds_file {
+            seeds.extend(read_seeds_file(path).map_err(Error::Io)?);
+        }
+    }
+    if seeds.is_empty() {
+        let seed = match &config.seed {
+            Some(seed) => seed.clone(),
+            None if config.keyring => keyring_seed()?,
+            None => algorithm::selected().default_seed().to_string(),
+        };
+        seeds.push(seed);
+    }
+    let mut unique: Vec<String> = Vec::new();
+    for seed in seeds {
+        if !unique.contains(&seed) {
+            unique.push(seed);
+        }
+    }
+    for seed in &unique {
+        validate_seed(seed).map_err(Error::InvalidSeed)?;
+    }
+    Ok(unique)
+}
+
+// where resolve_seeds found the seeds, for -vv
+fn seed_source(args: &Args, config: &Config) -> String {
+    if args.seed_prompt {
+        return String::from("typed at the prompt");
+    }
+    if let Some(path) = &args.seed_file {
+        return format!("seed file '{}'", path);
+    }
+    let mut sources = Vec::new();
+    if let Some(seeds) = &args.seed {
+        if seeds.iter().any(|seed| seed == "-") {
+            sources.push(String::from("stdin"));
+        }
+        if seeds.iter().any(|seed| seed != "-") {
+            sources.push(String::from(if args.seed_from_env {
+                "RSPOTD_SEED"
+            } else {
+                "--seed"
+            }));
+        }
+    }
+    if let Some(path) = &args.seeds_file {
+        sources.push(format!("seeds file '{}'", path));
+    }
+    if !sources.is_empty() {
+        return sources.join(", ");
+    }
+    match (&config.seed, &args.profile) {
+        (Some(_), Some(profile)) => format!("the config file, with profile '{}'", profile),
+        (Some(_), None) => String::from("the config file"),
+        (None, _) if config.keyring => String::from("the OS keyring"),
+        (None, _) => String::from("the default seed"),
+    }
+}
+
+// the first and last date to generate for, if a range was asked for in any form
+fn resolve_range(args: &Args) -> Result<Option<(NaiveDate, NaiveDate)>, Error> {
+    let (start, end) = if let Some(range) = &args.range {
+        // a missing end, or .. in place of either date, means today
+        let endpoint = |date: Option<&String>| match date.map(|date| date.as_str()) {
+            None | Some("..") => try_parse_date(&current_date()).map_err(Error::InvalidDate),
+            Some(option) if option.starts_with("--") => {
+                // values may start with a hyphen, so an option right after an open-ended
+                // range is taken as its end
+                Err(Error::Usage(format!(
+                    "'{}' was read as the end of the range; give the end date (.. for today) or move the option before --range.",
+                    option
+                )))
+            }
+            Some(date) => try_parse_date(&resolve_date(date)).map_err(Error::InvalidDate),
+        };
+        (endpoint(range.first())?, endpoint(range.get(1))?)
+    } else if let Some(count) = args.days {
+        let start = match args.date.as_deref() {
+            Some([date]) => try_parse_date(&resolve_date(date)).map_err(Error::InvalidDate)?,
+            Some(_) => return Err(Error::Usage(String::from("--days takes a single start date."))),
+            None => try_parse_date(&current_date()).map_err(Error::InvalidDate)?,
+        };
+        // rspotd only takes years of four digits
+        let end = start
+            .checked_add_days(Days::new(u64::from(count) - 1))
+            .filter(|end| end.year() <= 9999);
+        match end {
+            Some(end) => (start, end),
+            None => {
+                return Err(Error::InvalidDate(String::from(
+                    "Invalid date range. End date is out of range.",
+                )))
+            }
+        }
+    } else if let Some(month) = &args.month {
+        month_range(month).map_err(Error::InvalidDate)?
+    } else if let Some(year) = &args.year {
+        year_range(year).map_err(Error::InvalidDate)?
+    } else if let Some(week) = &args.week {
+        week_range(week).map_err(Error::InvalidDate)?
+    } else {
+        return Ok(None);
+    };
+    if end < start {
+        return Err(Error::InvalidDate(String::from(
+            "Invalid date range. Beginning date must not occur after end date.",
+        )));
+    }
+    Ok(Some((start, end)))
+}
+
+// print every date in the range whose password matches, failing if there are none
+fn find_password(
+    seeds: &[String],
+    password: &str,
+    (begin, end): (&str, &str),
+    date_format: &str,
+    out: &mut dyn Write,
+) -> Result<ExitCode, Error> {
+    let begin = try_parse_date(&resolve_date(begin)).map_err(Error::InvalidDate)?;
+    let end = try_parse_date(&resolve_date(end)).map_err(Error::InvalidDate)?;
+    if end < begin {
+        return Err(Error::InvalidDate(String::from(
+            "Invalid date range. Beginning date must not occur after end date.",
+        )));
+    }
+    let mut found = false;
+    for seed in seeds {
+        let passwords = generate_range(begin, end, seed).map_err(|err| err.to_string())?;
+        for (date, potd) in passwords {
+            if potd.eq_ignore_ascii_case(password.trim()) {
+                found = true;
+                if seeds.len() > 1 {
+                    writeln!(out, "{} (seed {})", date.format(date_format), seed_fingerprint(seed))?;
+                } else {
+                    writeln!(out, "{}", date.format(date_format))?;
+                }
+            }
+        }
+    }
+    if !found {
+        writeln!(out, "No date in the range produces that password.")?;
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+// fail unless one of the seeds produces the password for the date
+fn verify_password(
+    seeds: &[String],
+    password: &str,
+    date: &str,
+    out: &mut dyn Write,
+) -> Result<ExitCode, Error> {
+    try_parse_date(date).map_err(Error::InvalidDate)?;
+    for seed in seeds {
+        let potd = generate(date, seed).map_err(|err| err.to_string())?;
+        if potd.eq_ignore_ascii_case(password.trim()) {
+            if seeds.len() > 1 {
+                writeln!(out, "Password matches {} for seed {}.", date, seed_fingerprint(seed))?;
+            } else {
+                writeln!(out, "Password matches {}.", date)?;
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+    writeln!(out, "Password does not match {}.", date)?;
+    Ok(ExitCode::FAILURE)
+}
+
+// the Terraform external data source protocol: a JSON object of strings in, such as
+// {"date": "2024-07-01", "seed": "..."}, and one out; Terraform shows stderr if this fails
+fn answer_external(seeds: &[String], out: &mut dyn Write) -> Result<(), Error> {
+    let mut query = String::new();
+    io::stdin()
+        .read_to_string(&mut query)
+        .map_err(|err| Error::Io(format!("Unable to read query from stdin: {}", err)))?;
+    let query: BTreeMap<String, String> = if query.trim().is_empty() {
+        BTreeMap::new()
+    } else {
+        serde_json::from_str(&query).map_err(|err| {
+            Error::Usage(format!(
+                "Unable to parse query: {}. Expected a JSON object of strings such as {{\"date\": \"2024-07-01\"}}.",
+                err
+            ))
+        })?
+    };
+    if let Some(key) = query.keys().find(|key| *key != "date" && *key != "seed") {
+        return Err(Error::Usage(format!(
+            "Unknown query key '{}'; only date and seed are accepted.",
+            key
+        )));
+    }
+    // Terraform passes optional values as empty strings
+    let given = |key: &str| query.get(key).filter(|value| !value.is_empty());
+    let seed = match given("seed") {
+        Some(seed) => {
+            validate_seed(seed).map_err(Error::InvalidSeed)?;
+            seed
+        }
+        None if seeds.len() == 1 => &seeds[0],
+        None => {
+            return Err(Error::Usage(String::from(
+                "Several seeds are configured; give the one to use as seed in the query.",
+            )))
+        }
+    };
+    let date = given("date").map(|date| resolve_date(date)).unwrap_or_else(current_date);
+    let date = try_parse_date(&date).map_err(Error::InvalidDate)?;
+    let potd = generate(&date.format("%Y-%m-%d").to_string(), seed).map_err(|err| err.to_string())?;
+    audit::record("external", None, seed, date, &potd).map_err(Error::Delivery)?;
+    let result = serde_json::json!({
+        "password": potd,
+        "date": date.format("%Y-%m-%d").to_string(),
+        "seed_id": seed_fingerprint(seed),
+    });
+    writeln!(out, "{}", result)?;
+    Ok(())
+}
+
+fn manage_seed(
+    action: &SeedCommand,
+    config_path: Option<&str>,
+    profile: Option<&str>,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> Result<(), Error> {
+    match action {
+        SeedCommand::Store => {
+            let seed = read_seed().map_err(Error::Io)?;
+            validate_seed(&seed).map_err(Error::InvalidSeed)?;
+            store_keyring_seed(&seed)?;
+            writeln!(out, "Seed stored in the OS keyring.")?;
+        }
+        SeedCommand::Use { off } => {
+            if !off {
+                // fail now rather than on the next run if nothing has been stored
+                keyring_seed()?;
+            }
+            let path = set_config_value(config_path, &["keyring"], toml_edit::value(!off))?;
+            if *off {
+                writeln!(out, "Stored seed will no longer be used automatically ({}).", path.display())?;
+            } else {
+                writeln!(out, "Stored seed will be used when no seed is given ({}).", path.display())?;
+            }
+        }
+        SeedCommand::Forget => {
+            forget_keyring_seed()?;
+            writeln!(out, "Seed removed from the OS keyring.")?;
+        }
+        SeedCommand::New { length, save, store } => {
+            let seed = random_seed(*length as usize);
+            writeln!(out, "{}", seed)?;
+            if *save {
+                let keys = match profile {
+                    Some(profile) => vec!["profiles", profile, "seed"],
+                    None => vec!["seed"],
+                };
+                let path = set_config_value(config_path, &keys, toml_edit::value(&seed))?;
+                let _ = writeln!(err, "Seed saved to {}.", path.display());
+            }
+            if *store {
+                store_keyring_seed(&seed)?;
+                let _ = writeln!(err, "Seed stored in the OS keyring.");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run the command line given in `args`, the program name first, writing its output to
+/// `stdout` and its messages to `stderr`, and give the status to exit with. Interactive
+/// commands (the tui, repl and first-run questions) and the logs of the long-running ones
+/// still use the process's own terminal.
+pub fn run(
+    args: impl IntoIterator<Item = OsString>,
+    mut stdout: impl Write,
+    mut stderr: impl Write,
+) -> ExitCode {
+    let args: Vec<OsString> = args.into_iter().collect();
+    // run again by ssh to answer its prompts, see ssh::ssh
+    if let Some(password) = std::env::var_os(ssh::ASKPASS_VARIABLE) {
+        return ssh::askpass(&password, args.get(1).map(OsString::as_os_str), stdout);
+    }
+    // nothing at all given on the command line
+    let bare = args.len() == 1;
+    let json = wants_json_errors(&args);
+    let parsed = Args::command().try_get_matches_from(args).and_then(|matches| {
+        let mut args = Args::from_arg_matches(&matches)?;
+        args.seed_from_env = matches.value_source("seed") == Some(ValueSource::EnvVariable);
+        // RSPOTD_SEED is only a default, so the prompt and a seed file win over it but not
+        // over --seed
+        let other = match (args.seed_prompt, &args.seed_file) {
+            (true, _) => Some("--seed-prompt"),
+            (false, Some(_)) => Some("--seed-file <PATH>"),
+            (false, None) => None,
+        };
+        if let Some(other) = other {
+            if !args.seed_from_env && args.seed.is_some() {
+                return Err(Args::command().error(
+                    ErrorKind::ArgumentConflict,
+                    format!("the argument '{}' cannot be used with '--seed <SEED>'", other),
+                ));
+            }
+            args.seed = None;
+            args.seed_from_env = false;
+        }
+        Ok(args)
+    });
+    let args = match parsed {
+        Ok(args) => args,
+        Err(err) if json && err.use_stderr() => {
+            // tips and the pointer to --help make up the hint, the rest is the message
+            let rendered = err.render().to_string();
+            let (mut message, mut hint) = (Vec::new(), Vec::new());
+            for line in rendered.lines().map(str::trim) {
+                if let Some(tip) = line.strip_prefix("tip: ") {
+                    hint.push(format!("{}.", tip.trim_end_matches('.')));
+                } else if line.starts_with("For more information") {
+                    hint.push(line.to_string());
+                } else if !line.is_empty() && !line.starts_with("Usage:") {
+                    message.push(line.strip_prefix("error: ").unwrap_or(line));
+                }
+            }
+            let message = message.join(" ");
+            let hint = (!hint.is_empty()).then(|| hint.join(" "));
+            let _ = writeln!(stderr, "{}", error_json("usage", &message, hint.as_deref()));
+            return ExitCode::from(err.exit_code() as u8);
+        }
+        Err(err) => {
+            // --help and --version go to stdout, styled only when it's the terminal
+            let styled = err.render();
+            let _ = match (err.use_stderr(), io::stderr().is_terminal(), io::stdout().is_terminal()) {
+                (true, true, _) => write!(stderr, "{}", styled.ansi()),
+                (true, false, _) => write!(stderr, "{}", styled),
+                (false, _, true) => write!(stdout, "{}", styled.ansi()),
+                (false, _, false) => write!(stdout, "{}", styled),
+            };
+            return ExitCode::from(err.exit_code() as u8);
+        }
+    };
+    match cli(args, bare, &mut stdout, &mut stderr) {
+        Ok(code) => code,
+        Err(Error::ClosedPipe) => ExitCode::SUCCESS,
+        Err(err) => {
+            // stdout may already have part of the output on it, so errors never go there
+            let _ = if json {
+                writeln!(stderr, "{}", error_json(err.kind(), &err.to_string(), err.hint()))
+            } else {
+                writeln!(stderr, "{}", err)
+            };
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn cli(mut args: Args, bare: bool, out: &mut dyn Write, err: &mut dyn Write) -> Result<ExitCode, Error> {
+    let command = args.command.take();
+    init_logging(args.log_level.as_deref(), &command);
+    // nothing carries over from an earlier run in the same process
+    algorithm::reset();
+    if let Some(input_format) = &args.input_date_format {
+        validate_date_format(input_format).map_err(Error::Usage)?;
+    }
+    set_input_format(args.input_date_format.as_deref());
+    set_timezone(if args.utc { Some(Tz::UTC) } else { args.timezone });
+    audit::set_sink(args.log_sink.as_deref());
+    match &command {
+        Some(Command::Completions { shell }) => {
+            let mut command = Args::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(*shell, &mut command, name, out);
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Man { out_dir }) => {
+            write_man_pages(out_dir.as_deref(), out)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Seed { action }) => {
+            manage_seed(action, args.config.as_deref(), args.profile.as_deref(), out, err)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        #[cfg(feature = "db")]
+        Some(Command::Db {
+            file,
+            action: Some(DbCommand::Query { date, seed_id }),
+        }) => {
+            let date = date.as_deref().map(resolve_date).unwrap_or_else(current_date);
+            let date = try_parse_date(&date).map_err(Error::InvalidDate)?;
+            let rows = db::query(&output_file(file), date, seed_id.as_deref())?;
+            for row in &rows {
+                writeln!(out, "{}\t{}\t{}", row.date, row.seed_id, row.potd)?;
+            }
+            if rows.is_empty() {
+                return Ok(ExitCode::FAILURE);
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        _ => {}
+    }
+
+    let mut config = load_config(args.config.as_deref())?;
+    if args.all_profiles {
+        let format = args.format.clone().or(config.format.clone());
+        print_profile_des(&config, format.as_deref().unwrap_or("text"), out)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if let Some(Command::Fleet) = &command {
+        if args.seed.is_some() || args.seed_prompt || args.seed_file.is_some() || args.seeds_file.is_some() || args.profile.is_some() {
+            return Err(Error::Usage(String::from(
+                "fleet uses the seeds in the config file; leave out --seed, --seed-file, --seeds-file, --seed-prompt and --profile.",
+            )));
+        }
+        if args.output.as_deref().is_some_and(|output| output != "-") {
+            return Err(Error::Usage(String::from("fleet writes to stdout; redirect it rather than giving --output.")));
+        }
+        let date = match args.date.as_deref() {
+            None if args.range.is_none() => current_date(),
+            Some([date]) => resolve_date(date),
+            _ => return Err(Error::Usage(String::from("fleet generates for a single date; give at most one --date and no --range."))),
+        };
+        let date = try_parse_date(&date).map_err(Error::InvalidDate)?;
+        let format = args.format.clone().or(config.format.clone()).unwrap_or_else(|| String::from("text"));
+        if !FLEET_FORMATS.contains(&format.as_str()) {
+            return Err(Error::Usage(format!(
+                "fleet can't be written as --format {}; use one of: {}.",
+                format,
+                FLEET_FORMATS.join(", ")
+            )));
+        }
+        let rows = fleet_rows(&config, args.algorithm.as_deref(), date)?;
+        writeln!(out, "{}", format_fleet(&format, date, &rows, args.header))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if let Some(Command::Device { action: DeviceCommand::List }) = &command {
+        print_devices(&config, out)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    // the device's seed is the point of naming it, so it can't be overridden
+    let command = if let Some(Command::Device { action: DeviceCommand::Potd { name } }) = command {
+        if args.seed.is_some() || args.seed_prompt || args.seed_file.is_some() || args.seeds_file.is_some() || args.profile.is_some() {
+            return Err(Error::Usage(String::from(
+                "device potd uses the device's seed; leave out --seed, --seed-file, --seeds-file, --seed-prompt and --profile.",
+            )));
+        }
+        config = config.select_device(&name)?;
+        None
+    } else {
+        command
+    };
+    if let Some(profile) = &args.profile {
+        config = config.select_profile(profile)?;
+    }
+    let name = args.algorithm.as_deref().or(config.algorithm.as_deref()).unwrap_or("arris");
+    algorithm::select(name).map_err(Error::Usage)?;
+    // a bare invocation would print the default seed's password, which is easily mistaken
+    // for the one that works on the user's own devices
+    if args.seed.is_none() && config.seed.is_none() && !config.keyring && wizard::wanted(bare) {
+        let Some(answers) = wizard::ask()? else {
+            return Ok(ExitCode::FAILURE);
+        };
+        args.seed = answers.seed.map(|seed| vec![seed]);
+        match answers.dates.len() {
+            1 => args.date = Some(answers.dates),
+            2 => args.range = Some(answers.dates),
+            _ => {}
+        }
+        args.output = answers.output;
+    }
+    // "-" is stdout, so wrapper scripts can pass a file name or "-" in the same place
+    if args.output.as_deref() == Some("-") {
+        args.output = None;
+    }
+    let stdin_readers = [args.dates_from.as_deref(), args.exclude_from.as_deref()]
+        .into_iter()
+        .flatten()
+        .chain(args.seed.iter().flatten().map(|seed| seed.as_str()))
+        .filter(|source| *source == "-")
+        .count();
+    if stdin_readers > 1 {
+        return Err(Error::Usage(String::from(
+            "Only one of --seed, --dates-from and --exclude-from can read from stdin.",
+        )));
+    }
+    let seeds = resolve_seeds(&args, &config)?;
+    let seeds_from = seed_source(&args, &config);
+    let range = resolve_range(&args)?;
+    let weekdays = if args.weekends {
+        vec![Weekday::Sat, Weekday::Sun]
+    } else if args.workdays {
+        vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]
+    } else {
+        args.weekdays.clone().unwrap_or_default()
+    };
+    let mut exclude: Vec<NaiveDate> = args
+        .exclude
+        .iter()
+        .flatten()
+        .map(|date| try_parse_date(&resolve_date(date)))
+        .collect::<Result<_, _>>()
+        .map_err(Error::InvalidDate)?;
+    if let Some(source) = &args.exclude_from {
+        let (dates, skipped) = read_dates(source)?;
+        for warning in &skipped {
+            let _ = writeln!(err, "{}", warning);
+        }
+        if !skipped.is_empty() {
+            return Err(Error::InvalidDate(format!(
+                "Unable to read the dates to exclude from '{}'.",
+                source
+            )));
+        }
+        for date in &dates {
+            exclude.push(try_parse_date(date).map_err(Error::InvalidDate)?);
+        }
+    }
+    let filter = DateFilter {
+        step: args.step.unwrap_or(1),
+        weekdays,
+        exclude,
+    };
+
+    // command line values and environment variables take precedence over the config file
+    let format = match args.format {
+        Some(format) if args.export && format != "env" => {
+            return Err(Error::Usage(format!(
+                "--export prints env output and can't be used with --format {}.",
+                format
+            )));
+        }
+        _ if args.export => String::from("env"),
+        format => format
+            .or(config.format)
+            .unwrap_or_else(|| String::from("text")),
+    };
+
+    let date_format = args
+        .date_format
+        .or(config.date_format)
+        .unwrap_or_else(|| String::from("%Y-%m-%d"));
+
+    validate_date_format(&date_format).map_err(Error::Usage)?;
+    if args.explain_format {
+        let today = try_parse_date(&current_date()).map_err(Error::InvalidDate)?;
+        writeln!(out, "{}", explain_date_format(&date_format, args.locale, today))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(output) = args.output.as_deref().filter(|output| per_date_output(output)) {
+        output_path(output, today()).map_err(Error::Usage)?;
+    }
+    if let Some(template) = &args.template {
+        validate_template(template).map_err(Error::Usage)?;
+    }
+
+    if args.verbose >= 2 {
+        let fingerprints: Vec<String> = seeds.iter().map(|seed| seed_fingerprint(seed)).collect();
+        let config_file = args
+            .config
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| default_config_path().filter(|path| path.exists()));
+        let timezone = match (args.utc, args.timezone) {
+            (true, _) => String::from("UTC"),
+            (false, Some(zone)) => zone.to_string(),
+            (false, None) => String::from("local"),
+        };
+        let _ = writeln!(
+            err,
+            "Seed: {} (fingerprint {})",
+            seeds_from,
+            fingerprints.join(", ")
+        );
+        let _ = writeln!(err, "Algorithm: {}", algorithm::selected().name());
+        let _ = writeln!(
+            err,
+            "Config file: {}",
+            config_file.map_or(String::from("none"), |path| path.display().to_string())
+        );
+        let _ = writeln!(err, "Format: {}", format);
+        let _ = writeln!(err, "Date format: {}", date_format);
+        let _ = writeln!(err, "Time zone: {}", timezone);
+        if let Some(locale) = args.locale {
+            let _ = writeln!(err, "Locale: {:?}", locale);
+        }
+    }
+
+    if let Some(Command::Verify { date, password }) = &command {
+        let date = date.as_deref().map(resolve_date).unwrap_or_else(current_date);
+        return verify_password(&seeds, password, &date, out);
+    }
+
+    if let Some(Command::Serve {
+        listen,
+        auth_token,
+        rate_limit,
+    }) = command
+    {
+        let options = ServeOptions {
+            listen,
+            auth_token,
+            rate_limit,
+        };
+        serve(&options, &seeds)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    #[cfg(feature = "grpc")]
+    if let Some(Command::Grpc { listen, auth_token }) = command {
+        grpc::serve_grpc(&listen, auth_token, &seeds)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    #[cfg(feature = "check")]
+    if let Some(Command::Check {
+        host,
+        method,
+        port,
+        user,
+        model,
+        warning,
+        critical,
+        timeout,
+    }) = command
+    {
+        let [seed] = &seeds[..] else {
+            return Err(Error::Usage(String::from(
+                "check logs in with a single seed; give one with --seed or --profile.",
+            )));
+        };
+        let date = current_date();
+        let potd = generate(&date, seed).map_err(|err| err.to_string())?;
+        let today = try_parse_date(&date).map_err(Error::InvalidDate)?;
+        audit::record("check", None, seed, today, &potd).map_err(Error::Delivery)?;
+        let options = check::CheckOptions {
+            host,
+            method,
+            port,
+            user,
+            model,
+            warning,
+            critical,
+            timeout: std::time::Duration::from_secs(timeout),
+        };
+        let (status, line) = check::check(&options, &potd);
+        writeln!(out, "{}", line)?;
+        return Ok(ExitCode::from(status as u8));
+    }
+
+    #[cfg(feature = "login")]
+    if let Some(Command::Login {
+        host,
+        model,
+        user,
+        port,
+        https,
+        print_cookie,
+        timeout,
+    }) = command
+    {
+        let [seed] = &seeds[..] else {
+            return Err(Error::Usage(String::from(
+                "login uses a single seed; give one with --seed or --profile.",
+            )));
+        };
+        let date = current_date();
+        let potd = generate(&date, seed).map_err(|err| err.to_string())?;
+        let today = try_parse_date(&date).map_err(Error::InvalidDate)?;
+        audit::record("login", None, seed, today, &potd).map_err(Error::Delivery)?;
+        let options = webui::WebLogin {
+            host,
+            model,
+            user,
+            port,
+            https,
+            timeout: std::time::Duration::from_secs(timeout),
+        };
+        let target = format!("{}@{} ({})", options.user(), options.host, options.model);
+        let outcome = webui::login(&options, &potd)
+            .map_err(|err| Error::Io(format!("Unable to reach {}: {}", target, err)))?;
+        return match outcome {
+            webui::WebOutcome::Accepted(Some(cookie)) if print_cookie => {
+                writeln!(out, "{}", cookie)?;
+                Ok(ExitCode::SUCCESS)
+            }
+            webui::WebOutcome::Accepted(None) if print_cookie => Err(Error::Failed(format!(
+                "Logged in to {}, but it didn't set a session cookie.",
+                target
+            ))),
+            webui::WebOutcome::Accepted(_) => {
+                writeln!(out, "Logged in to {} with today's password.", target)?;
+                Ok(ExitCode::SUCCESS)
+            }
+            webui::WebOutcome::Rejected(said) => Err(Error::Failed(format!(
+                "{} rejected today's password: {}",
+                target, said
+            ))),
+            webui::WebOutcome::Unexpected(what) => {
+                Err(Error::Failed(format!("Unable to log in to {}: {}", target, what)))
+            }
+        };
+    }
+
+    if let Some(Command::Ssh {
+        host,
+        user,
+        port,
+        command,
+    }) = command
+    {
+        let [seed] = &seeds[..] else {
+            return Err(Error::Usage(String::from(
+                "ssh logs in with a single seed; give one with --seed or --profile.",
+            )));
+        };
+        let date = current_date();
+        let potd = generate(&date, seed).map_err(|err| err.to_string())?;
+        let today = try_parse_date(&date).map_err(Error::InvalidDate)?;
+        audit::record("ssh", None, seed, today, &potd).map_err(Error::Delivery)?;
+        let options = ssh::SshOptions {
+            host,
+            user,
+            port,
+            command,
+        };
+        return ssh::ssh(&options, &potd);
+    }
+
+    if let Some(Command::Telnet {
+        host,
+        port,
+        user,
+        timeout,
+    }) = command
+    {
+        let [seed] = &seeds[..] else {
+            return Err(Error::Usage(String::from(
+                "telnet logs in with a single seed; give one with --seed or --profile.",
+            )));
+        };
+        let date = current_date();
+        let potd = generate(&date, seed).map_err(|err| err.to_string())?;
+        let today = try_parse_date(&date).map_err(Error::InvalidDate)?;
+        let timeout = std::time::Duration::from_secs(timeout);
+        let session = Telnet::connect(&host, port, timeout)
+            .map_err(|err| Error::Io(format!("Unable to connect to {}:{}: {}", host, port, err)))?;
+        audit::record("telnet", None, seed, today, &potd).map_err(Error::Delivery)?;
+        return match session.interact(&user, &potd, timeout) {
+            Ok(Login::Rejected(said)) => Err(Error::Failed(format!(
+                "{} rejected today's password: {}",
+                host, said
+            ))),
+            Ok(_) => Ok(ExitCode::SUCCESS),
+            Err(err) => Err(Error::Io(format!("Telnet session with {} failed: {}", host, err))),
+        };
+    }
+
+    if let Some(Command::External) = &command {
+        answer_external(&seeds, out)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Which { password, range }) = &command {
+        return find_password(&seeds, password, (&range[0], &range[1]), &date_format, out);
+    }
+
+    let options = FormatOptions {
+        format,
+        date_format,
+        locale: args.locale,
+        // a header in the middle of the rows would be read as one
+        header: args.header && !appends_to_data(args.append, args.output.as_deref()),
+        title: args.title,
+        password_only: args.quiet,
+        template: args.template,
+        color: use_color(&args.color, args.output.is_some() || sends_elsewhere(&command)),
+        grouped: seeds.len() > 1,
+        export: args.export,
+    };
+
+    let file_options = OutputOptions {
+        append: args.append,
+        force: args.force,
+        backup: args.backup,
+        mode: args.mode,
+        mkdir: args.mkdir,
+        #[cfg(feature = "compress")]
+        compress: args.compress,
+        #[cfg(feature = "encrypt")]
+        encrypt_to: args.encrypt_to,
+    };
+
+    if binary_format(&options.format) {
+        if sends_elsewhere(&command) {
+            return Err(Error::Usage(format!(
+                "--format {} can only be written to a file with --output, or piped.",
+                options.format
+            )));
+        }
+        // a spreadsheet dumped on the terminal is of no use to anyone
+        if args.output.is_none() && io::stdout().is_terminal() {
+            return Err(Error::Usage(format!(
+                "--format {} is binary; write it to a file with --output, or pipe it.",
+                options.format
+            )));
+        }
+    }
+
+    if args.des {
+        for seed in &seeds {
+            let des = seed_to_des(seed).map_err(|err| err.to_string())?;
+            if options.grouped {
+                writeln!(out, "{}: {}", seed_fingerprint(seed), des)?;
+            } else {
+                writeln!(out, "{}", des)?;
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    #[cfg(feature = "webhook")]
+    let webhook = args.webhook.clone().map(|url| webhook::Webhook {
+        url,
+        secret: args.webhook_secret.clone(),
+        retries: args.webhook_retries,
+    });
+
+    #[cfg(feature = "tui")]
+    if let Some(Command::Tui) = &command {
+        tui::run(&options, &seeds)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Repl) = &command {
+        repl::run(&options, &seeds);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(Command::Watch) = &command {
+        // the file is the watch's own, rewritten on every rotation and on every restart
+        let file_options = OutputOptions {
+            force: true,
+            ..file_options
+        };
+        watch(|date, _| -> Result<(), Error> {
+            let entries = day_entries(&seeds, date)?;
+            audit_entries("watch", &entries)?;
+            let potd = format_potd(&options, &entries)?;
+            match &args.output {
+                Some(output) => {
+                    if args.verbose >= 1 {
+                        writeln!(out, "{}", potd)?;
+                    }
+                    save_output(&options, &entries, &potd, output, &file_options)?;
+                }
+                None => writeln!(out, "{}", potd)?,
+            }
+            #[cfg(feature = "webhook")]
+            if let Some(webhook) = &webhook {
+                // a missed delivery shouldn't stop the watch
+                if let Err(err) = webhook::deliver(webhook, &format_json_payload(&options, &entries)) {
+                    tracing::error!("{}", err);
+                }
+            }
+            Ok(())
+        })?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    #[cfg(feature = "mqtt")]
+    if let Some(Command::Publish {
+        target: PublishTarget::Mqtt { settings, once },
+    }) = command
+    {
+        mqtt::publish(&settings, once, |date| {
+            let entries = day_entries(&seeds, date)?;
+            audit_entries("mqtt", &entries)?;
+            Ok(format_potd(&options, &entries)?)
+        })?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // determine whether date or range and set potd value
+    let mut entries: Vec<Entry> = Vec::new();
+    let potd;
+    // whether lines of --dates-from were reported and left out
+    let mut skipped_dates = false;
+    if let Some((begin, end)) = range {
+        // the lines streaming into the terminal already show how far along the range is
+        let progress = if args.output.is_none() && io::stdout().is_terminal() {
+            ProgressBar::hidden()
+        } else {
+            range_progress(begin, end, seeds.len())
+        };
+        let batches = |each| {
+            range_batches(&seeds, (begin, end), args.reverse, &filter, args.limit, &progress, each)
+        };
+        // with nothing afterwards that needs every entry, the range is written as it's
+        // generated rather than held in memory, so `| head` gets its lines right away
+        let streamed = command.is_none()
+            && !binary_format(&options.format)
+            && !args.output.as_deref().is_some_and(|output| {
+                writes_png(&options, Path::new(output)) || per_date_output(output)
+            });
+        #[cfg(feature = "webhook")]
+        let streamed = streamed && webhook.is_none();
+        #[cfg(feature = "clipboard")]
+        let streamed = streamed && !args.copy;
+        if streamed {
+            let path = args.output.as_deref().map(output_file);
+            let mut file = match &path {
+                Some(path) => Some(OutputFile::create(path, &file_options)?),
+                None => None,
+            };
+            let target: Box<dyn Write> = match &mut file {
+                Some(file) => Box::new(Echo {
+                    inner: file,
+                    echo: (args.verbose >= 1).then_some(&mut *out),
+                }),
+                // the caller's stdout, which the process's own line-buffers, so each line goes
+                // out as soon as it's written
+                None => Box::new(&mut *out),
+            };
+            let mut output = OutputWriter::new(&options, target, ": ");
+            batches(&mut |batch: Vec<Entry>| {
+                if args.verbose >= 3 {
+                    print_steps(err, &batch)?;
+                }
+                audit_entries("cli", &batch)?;
+                for entry in &batch {
+                    output
+                        .write(entry)
+                        .map_err(|err| write_failed(path.as_deref(), err))?;
+                }
+                Ok(())
+            })?;
+            progress.finish_and_clear();
+            output
+                .finish()
+                .map_err(|err| write_failed(path.as_deref(), err))?;
+            // only a range that was written out in full replaces the file
+            if let Some(file) = file {
+                file.commit()
+                    .map_err(|err| write_failed(path.as_deref(), err))?;
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        batches(&mut |batch: Vec<Entry>| {
+            if args.verbose >= 3 {
+                print_steps(err, &batch)?;
+            }
+            entries.extend(batch);
+            Ok(())
+        })?;
+        progress.finish_and_clear();
+        potd = render(&options, &entries, true)?;
+    } else {
+        let dates: Vec<String> = match (&args.date, &args.dates_from) {
+            (Some(dates), _) => dates.iter().map(|date| resolve_date(date)).collect(),
+            (None, Some(source)) => {
+                let (dates, skipped) = read_dates(source)?;
+                for warning in &skipped {
+                    let _ = writeln!(err, "{}", warning);
+                }
+                skipped_dates = !skipped.is_empty();
+                dates
+            }
+            (None, None) => vec![current_date()],
+        };
+        let parsed: Vec<NaiveDate> = dates
+            .iter()
+            .map(|date| try_parse_date(date))
+            .collect::<Result<_, _>>()
+            .map_err(Error::InvalidDate)?;
+        for seed in &seeds {
+            for (date, parsed) in dates.iter().zip(&parsed) {
+                let entry = Entry {
+                    seed: seed.to_string(),
+                    date: *parsed,
+                    potd: generate(date, seed).map_err(|err| err.to_string())?,
+                };
+                if args.verbose >= 3 {
+                    print_steps(err, std::slice::from_ref(&entry))?;
+                }
+                entries.push(entry);
+            }
+        }
+        // several explicit dates are laid out the same way as a range
+        if dates.len() > 1 || args.dates_from.is_some() {
+            potd = render(&options, &entries, true)?;
+        } else {
+            potd = render(&options, &entries, false)?;
+        }
+    }
+
+    audit_entries(
+        match &command {
+            #[cfg(feature = "email")]
+            Some(Command::Send { .. }) => "email",
+            #[cfg(feature = "notify")]
+            Some(Command::Notify { .. }) => "notify",
+            #[cfg(feature = "db")]
+            Some(Command::Db { .. }) => "db",
+            _ => "cli",
+        },
+        &entries,
+    )?;
+
+    #[cfg(feature = "db")]
+    if let Some(Command::Db { file, action: None }) = &command {
+        db::store(&output_file(file), &entries)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    #[cfg(feature = "email")]
+    if let Some(Command::Send {
+        target: SendTarget::Email { to, subject },
+    }) = &command
+    {
+        let subject = subject
+            .clone()
+            .or_else(|| config.email.as_ref().and_then(|email| email.subject.clone()))
+            .unwrap_or_else(|| default_subject(&entries));
+        let html = options.format == "html";
+        email::send_email(config.email.as_ref(), to, &subject, &potd, html)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    #[cfg(feature = "notify")]
+    if let Some(Command::Notify { service }) = command {
+        let service = match service {
+            NotifyService::Slack { url } => notify::Service::Slack(url),
+            NotifyService::Teams { url } => notify::Service::Teams(url),
+            NotifyService::Telegram {
+                token,
+                chat_id,
+                api_url,
+            } => notify::Service::Telegram {
+                api_url,
+                token,
+                chat_id,
+            },
+        };
+        notify::notify(&service, &default_subject(&entries), &options, &entries)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // determine output file, if any
+    if args.output.is_none() && binary_format(&options.format) {
+        out.write_all(&format_document(&options, &entries)?)?;
+    } else if args.output.is_none() {
+        writeln!(out, "{}", potd)?;
+    } else {
+        if args.verbose >= 1 && !binary_format(&options.format) {
+            writeln!(out, "{}", potd)?;
+        }
+        let user_input = args.output.unwrap();
+        save_output(&options, &entries, &potd, &user_input, &file_options)?;
+    }
+    #[cfg(feature = "webhook")]
+    if let Some(webhook) = &webhook {
+        webhook::deliver(webhook, &format_json_payload(&options, &entries))?;
+    }
+    #[cfg(feature = "clipboard")]
+    if args.copy {
+        let passwords: Vec<&str> = entries.iter().map(|entry| entry.potd.as_str()).collect();
+        clipboard::copy(&passwords.join("\n"), args.clear_after, err)?;
+    }
+    if skipped_dates {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
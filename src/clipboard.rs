@@ -0,0 +1,100 @@
+use arboard::Clipboard;
+use std::{
+    io::Write,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// Put the text on the clipboard, marked so clipboard managers leave it out of their history,
+/// and wipe it after `clear_after` seconds unless something else has been copied since.
+///
+/// On Linux the text disappears when the process exits, so this keeps running until the
+/// clipboard is cleared or something else is copied. What happened is reported on `stderr`.
+pub fn copy(text: &str, clear_after: Option<u64>, stderr: &mut dyn Write) -> Result<(), String> {
+    let mut clipboard =
+        Clipboard::new().map_err(|err| format!("Unable to open the clipboard: {}", err))?;
+    let deadline = clear_after.map(|seconds| Instant::now() + Duration::from_secs(seconds));
+    // arboard's X11/Wayland backend, where the clipboard only holds what a running process serves
+    let served_by_process = cfg!(all(
+        unix,
+        not(any(
+            target_os = "macos",
+            target_os = "android",
+            target_os = "emscripten"
+        ))
+    ));
+    let _ = match (clear_after, served_by_process) {
+        (Some(seconds), _) => writeln!(
+            stderr,
+            "Copied to the clipboard, it will be cleared in {} seconds.",
+            seconds
+        ),
+        (None, true) => writeln!(
+            stderr,
+            "Copied to the clipboard, it stays available until something else is copied or this is stopped."
+        ),
+        (None, false) => writeln!(stderr, "Copied to the clipboard."),
+    };
+    let wait = match deadline {
+        Some(deadline) => Wait::Until(deadline),
+        None => Wait::Forever,
+    };
+    set_text(&mut clipboard, text, wait)
+        .map_err(|err| format!("Unable to copy to the clipboard: {}", err))?;
+    let Some(deadline) = deadline else {
+        return Ok(());
+    };
+    sleep(deadline.saturating_duration_since(Instant::now()));
+    if clipboard.get_text().is_ok_and(|current| current == text) {
+        let _ = clipboard.clear();
+    }
+    Ok(())
+}
+
+/// Put the text on the clipboard without waiting, for callers like the tui that keep running
+/// and hold on to the clipboard anyway
+#[cfg(feature = "tui")]
+pub fn set(clipboard: &mut Clipboard, text: &str) -> Result<(), String> {
+    set_text(clipboard, text, Wait::No).map_err(|err| err.to_string())
+}
+
+// how long to keep serving the text where that's up to the process
+enum Wait {
+    #[cfg(feature = "tui")]
+    No,
+    Until(Instant),
+    Forever,
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+fn set_text(clipboard: &mut Clipboard, text: &str, wait: Wait) -> Result<(), arboard::Error> {
+    use arboard::SetExtLinux;
+    let set = clipboard.set().exclude_from_history();
+    // block while serving the text, until the deadline or until another process takes over
+    let set = match wait {
+        #[cfg(feature = "tui")]
+        Wait::No => set,
+        Wait::Until(deadline) => set.wait_until(deadline),
+        Wait::Forever => set.wait(),
+    };
+    set.text(text)
+}
+
+#[cfg(target_os = "macos")]
+fn set_text(clipboard: &mut Clipboard, text: &str, _wait: Wait) -> Result<(), arboard::Error> {
+    use arboard::SetExtApple;
+    clipboard.set().exclude_from_history().text(text)
+}
+
+#[cfg(windows)]
+fn set_text(clipboard: &mut Clipboard, text: &str, _wait: Wait) -> Result<(), arboard::Error> {
+    use arboard::SetExtWindows;
+    clipboard
+        .set()
+        .exclude_from_history()
+        .exclude_from_cloud()
+        .text(text)
+}
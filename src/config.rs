@@ -0,0 +1,361 @@
+use crate::error::Error;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Values read from the user's config file; anything given on the command line takes precedence.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub seed: Option<String>,
+    /// One of `algorithm::names()`, ARRIS if not given
+    pub algorithm: Option<String>,
+    pub format: Option<String>,
+    pub date_format: Option<String>,
+    /// Pull the seed from the OS keyring when no other seed is given
+    pub keyring: bool,
+    /// Named seeds for different device fleets, selected with --profile
+    pub profiles: BTreeMap<String, Profile>,
+    /// SMTP settings for `send email`
+    pub email: Option<EmailConfig>,
+    /// Named devices and the seed each one uses, for `device potd` and `device list`
+    pub devices: BTreeMap<String, Device>,
+}
+
+/// The `[email]` table, used by `send email`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+// still checked without the email feature, so a config file works with every build
+#[cfg_attr(not(feature = "email"), allow(dead_code))]
+pub struct EmailConfig {
+    pub host: String,
+    /// Defaults to the usual port for the TLS mode
+    pub port: Option<u16>,
+    /// "starttls" (the default), "tls" for implicit TLS, or "none"
+    #[serde(default = "default_email_tls")]
+    pub tls: String,
+    pub username: Option<String>,
+    /// Can also be given with RSPOTD_SMTP_PASSWORD instead of being stored here
+    pub password: Option<String>,
+    pub from: String,
+    /// Recipients used when none are given with --to
+    #[serde(default)]
+    pub to: Vec<String>,
+    pub subject: Option<String>,
+}
+
+fn default_email_tls() -> String {
+    String::from("starttls")
+}
+
+/// A named `[profiles.<name>]` table; its values replace the top-level ones when selected
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub seed: Option<String>,
+    pub algorithm: Option<String>,
+    pub format: Option<String>,
+    pub date_format: Option<String>,
+}
+
+/// A named `[devices.<name>]` table
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Device {
+    /// Falls back to the profile's seed, then the top-level one
+    pub seed: Option<String>,
+    /// The profile whose seed, algorithm, format and date format the device uses
+    pub profile: Option<String>,
+    /// Where the device is reached, shown by `device list`
+    pub host: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl Config {
+    /// Apply the named profile on top of the top-level values, failing if it doesn't exist
+    pub fn select_profile(mut self, name: &str) -> Result<Config, Error> {
+        let profile = match self.profiles.remove(name) {
+            Some(profile) => profile,
+            None if self.profiles.is_empty() => {
+                return Err(Error::Config(format!(
+                    "Unknown profile '{}', no profiles are configured.",
+                    name
+                )));
+            }
+            None => {
+                let names: Vec<&str> = self.profiles.keys().map(|name| name.as_str()).collect();
+                return Err(Error::Config(format!(
+                    "Unknown profile '{}', must be one of: {}.",
+                    name,
+                    names.join(", ")
+                )));
+            }
+        };
+        self.seed = profile.seed.or(self.seed);
+        self.algorithm = profile.algorithm.or(self.algorithm);
+        self.format = profile.format.or(self.format);
+        self.date_format = profile.date_format.or(self.date_format);
+        Ok(self)
+    }
+
+    /// Apply the named device's profile, if it has one, and then its seed, failing if the
+    /// device doesn't exist
+    pub fn select_device(mut self, name: &str) -> Result<Config, Error> {
+        let device = self.device(name)?;
+        let seed = device.seed.clone();
+        let profile = device.profile.clone();
+        if let Some(profile) = profile {
+            self = self.select_profile(&profile)?;
+        }
+        self.seed = seed.or(self.seed);
+        Ok(self)
+    }
+
+    /// The named device, failing if it doesn't exist
+    pub fn device(&self, name: &str) -> Result<&Device, Error> {
+        match self.devices.get(name) {
+            Some(device) => Ok(device),
+            None if self.devices.is_empty() => Err(Error::Config(format!(
+                "Unknown device '{}', no devices are configured.",
+                name
+            ))),
+            None => {
+                let names: Vec<&str> = self.devices.keys().map(|name| name.as_str()).collect();
+                Err(Error::Config(format!(
+                    "Unknown device '{}', must be one of: {}.",
+                    name,
+                    names.join(", ")
+                )))
+            }
+        }
+    }
+
+    /// The algorithm the named device uses, if one is configured for it at all
+    pub fn device_algorithm(&self, name: &str) -> Result<Option<&String>, Error> {
+        let device = self.device(name)?;
+        let profile = device
+            .profile
+            .as_ref()
+            .and_then(|profile| self.profiles.get(profile));
+        Ok(profile
+            .and_then(|profile| profile.algorithm.as_ref())
+            .or(self.algorithm.as_ref()))
+    }
+
+    /// The seed the named device uses, if one is configured for it at all
+    pub fn device_seed(&self, name: &str) -> Result<Option<&String>, Error> {
+        let device = self.device(name)?;
+        let profile = device
+            .profile
+            .as_ref()
+            .and_then(|profile| self.profiles.get(profile));
+        Ok(device
+            .seed
+            .as_ref()
+            .or(profile.and_then(|profile| profile.seed.as_ref()))
+            .or(self.seed.as_ref()))
+    }
+}
+
+/// Location of the config file, e.g. ~/.config/rspotd/config.toml on Linux,
+/// ~/Library/Application Support/rspotd/config.toml on macOS or
+/// %APPDATA%\rspotd\config.toml on Windows
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("rspotd").join("config.toml"))
+}
+
+/// Set a value in the config file, creating the file and any parent tables if needed
+/// and keeping existing comments and formatting intact
+pub fn set_config_value(
+    path: Option<&str>,
+    keys: &[&str],
+    value: toml_edit::Item,
+) -> Result<PathBuf, Error> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => default_config_path().ok_or_else(|| {
+            Error::Config(String::from(
+                "Unable to determine the config file location for this platform.",
+            ))
+        })?,
+    };
+    let contents = if path.exists() {
+        fs::read_to_string(&path).map_err(|err| {
+            Error::Config(format!(
+                "Unable to read config file '{}': {}",
+                path.display(),
+                err
+            ))
+        })?
+    } else {
+        String::new()
+    };
+    let mut document: toml_edit::DocumentMut =
+        contents.parse().map_err(|err: toml_edit::TomlError| {
+            Error::Config(format!(
+                "Unable to parse config file '{}': {}",
+                path.display(),
+                err.message()
+            ))
+        })?;
+    let (key, parents) = keys.split_last().unwrap();
+    let mut table = document.as_table_mut();
+    for parent in parents {
+        let item = table.entry(parent).or_insert_with(|| {
+            let mut parent_table = toml_edit::Table::new();
+            parent_table.set_implicit(true);
+            toml_edit::Item::Table(parent_table)
+        });
+        table = match item.as_table_mut() {
+            Some(table) => table,
+            None => {
+                return Err(Error::Config(format!(
+                    "Unable to update config file '{}', '{}' is not a table.",
+                    path.display(),
+                    parent
+                )));
+            }
+        };
+    }
+    table[key] = value;
+    if let Some(parent) = path.parent() {
+        if fs::create_dir_all(parent).is_err() {
+            return Err(Error::Config(format!(
+                "Unable to create config directory '{}'.",
+                parent.display()
+            )));
+        }
+    }
+    if let Err(err) = write_private(&path, &document.to_string()) {
+        return Err(Error::Config(format!(
+            "Unable to write config file '{}': {}",
+            path.display(),
+            err
+        )));
+    }
+    Ok(path)
+}
+
+// the config can hold seeds, so it's readable by its owner only (rw-------); the new version is
+// written next to it and renamed into place, so the old one is never left half written or
+// briefly readable by others
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    // replacing what a symlinked config points to rather than the link
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?
+        .to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, process::id()));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let result = options
+        .open(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, &path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Load the config file at the given path, or the default location if no path is given.
+///
+/// A missing file at the default location is not an error; an explicitly requested file must exist.
+pub fn load_config(path: Option<&str>) -> Result<Config, Error> {
+    let (path, required) = match path {
+        Some(path) => (PathBuf::from(path), true),
+        None => match default_config_path() {
+            Some(path) => (path, false),
+            None => return Ok(Config::default()),
+        },
+    };
+    if !path.exists() && !required {
+        return Ok(Config::default());
+    }
+    let contents = fs::read_to_string(&path).map_err(|err| {
+        Error::Config(format!(
+            "Unable to read config file '{}': {}",
+            path.display(),
+            err
+        ))
+    })?;
+    parse_config(&path, &contents)
+}
+
+fn parse_config(path: &Path, contents: &str) -> Result<Config, Error> {
+    let config: Config = toml::from_str(contents).map_err(|err| {
+        Error::Config(format!(
+            "Unable to parse config file '{}': {}",
+            path.display(),
+            err.message()
+        ))
+    })?;
+    validate_format(path, config.format.as_deref())?;
+    validate_algorithm(path, config.algorithm.as_deref())?;
+    if let Some(email) = &config.email {
+        if !["starttls", "tls", "none"].contains(&email.tls.as_str()) {
+            return Err(Error::Config(format!(
+                "Invalid email tls '{}' in config file '{}', must be one of: starttls, tls, none.",
+                email.tls,
+                path.display()
+            )));
+        }
+    }
+    for profile in config.profiles.values() {
+        validate_format(path, profile.format.as_deref())?;
+        validate_algorithm(path, profile.algorithm.as_deref())?;
+    }
+    for (name, device) in &config.devices {
+        if let Some(profile) = &device.profile {
+            if !config.profiles.contains_key(profile) {
+                return Err(Error::Config(format!(
+                    "Device '{}' in config file '{}' uses profile '{}', which isn't configured.",
+                    name,
+                    path.display(),
+                    profile
+                )));
+            }
+        }
+    }
+    Ok(config)
+}
+
+fn validate_format(path: &Path, format: Option<&str>) -> Result<(), Error> {
+    match format {
+        Some(format) if !crate::format::FORMATS.contains(&format) => Err(Error::Config(format!(
+            "Invalid format '{}' in config file '{}', must be one of: {}.",
+            format,
+            path.display(),
+            crate::format::FORMATS.join(", ")
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn validate_algorithm(path: &Path, algorithm: Option<&str>) -> Result<(), Error> {
+    match algorithm {
+        Some(algorithm) if crate::algorithm::by_name(algorithm).is_none() => {
+            Err(Error::Config(format!(
+                "Invalid algorithm '{}' in config file '{}', must be one of: {}.",
+                algorithm,
+                path.display(),
+                crate::algorithm::names().join(", ")
+            )))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,392 @@
+use crate::error::Error;
+use chrono::{
+    format::ParseErrorKind, DateTime, Datelike, Days, Local, Months, NaiveDate, NaiveTime,
+    TimeDelta, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use std::{
+    fs,
+    io::{stdin, Read},
+    sync::{PoisonError, RwLock},
+};
+
+// the time zone that decides what today is, if not the local one
+static TIMEZONE: RwLock<Option<Tz>> = RwLock::new(None);
+
+/// Take today's date, and the date of epoch timestamps, in the given time zone instead of
+/// the local one, or in the local one again if none is given
+pub fn set_timezone(zone: Option<Tz>) {
+    *TIMEZONE.write().unwrap_or_else(PoisonError::into_inner) = zone;
+}
+
+fn timezone() -> Option<Tz> {
+    *TIMEZONE.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Parse a time zone given on the command line, e.g. America/Chicago
+pub fn timezone_arg(zone: &str) -> Result<Tz, String> {
+    zone.parse().map_err(|_| {
+        format!(
+            "unknown time zone '{}', expected an IANA name such as America/Chicago or UTC",
+            zone
+        )
+    })
+}
+
+// an extra format dates may be given in, besides YYYY-MM-DD and relative expressions
+static INPUT_FORMAT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Also accept dates in the given strftime format, e.g. %m/%d/%Y, or only the built-in ones
+/// if none is given
+pub fn set_input_format(input_format: Option<&str>) {
+    *INPUT_FORMAT.write().unwrap_or_else(PoisonError::into_inner) =
+        input_format.map(str::to_string);
+}
+
+fn input_format() -> Option<String> {
+    INPUT_FORMAT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Today's date in the time zone set with --timezone, or the local one
+pub fn today() -> NaiveDate {
+    match timezone() {
+        Some(zone) => Utc::now().with_timezone(&zone).date_naive(),
+        None => Local::now().date_naive(),
+    }
+}
+
+/// The moment the date next rolls over in the time zone set with --timezone, or the local one
+pub fn next_midnight() -> DateTime<Utc> {
+    let tomorrow = today() + Days::new(1);
+    match timezone() {
+        Some(zone) => start_of_day(&zone, tomorrow),
+        None => start_of_day(&Local, tomorrow),
+    }
+}
+
+// midnight is skipped in zones that start daylight saving time at 00:00, in which case the
+// day starts at the first local time that exists
+fn start_of_day<Z: TimeZone>(zone: &Z, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    (0..=24 * 4)
+        .map(|quarter| midnight + TimeDelta::minutes(15 * quarter))
+        .find_map(|time| zone.from_local_datetime(&time).earliest())
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+/// Today's date in YYYY-MM-DD format
+pub fn current_date() -> String {
+    today().format("%Y-%m-%d").to_string()
+}
+
+/// Turn a date given on the command line into YYYY-MM-DD, expanding relative expressions
+/// such as `tomorrow`, `next monday`, `+3 days` or `today+7`, epoch timestamps such as
+/// `@1735689600` and dates in the --input-date-format; anything else is passed through for
+/// validation
+pub fn resolve_date(input: &str) -> String {
+    let date = relative_date(input, today()).or_else(|| {
+        input_format()
+            .and_then(|input_format| NaiveDate::parse_from_str(input.trim(), &input_format).ok())
+    });
+    match date {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => input.to_string(),
+    }
+}
+
+// the supported expressions, all relative to today unless an explicit base date is given:
+//   today, tomorrow, yesterday
+//   next/last/this <weekday>, next/last week/month/year
+//   +N [unit], -N [unit], in N <unit>, N <unit> ago
+//   <base>+N [unit], <base>-N [unit], where base is a keyword or YYYY-MM-DD
+//   @<seconds since the UNIX epoch>, taken in the same time zone as today
+fn relative_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+    match words.as_slice() {
+        ["today"] => Some(today),
+        ["tomorrow"] => Some(today + Days::new(1)),
+        ["yesterday"] => Some(today - Days::new(1)),
+        [timestamp] if timestamp.starts_with('@') => {
+            let seconds: i64 = timestamp[1..].parse().ok()?;
+            let datetime = DateTime::from_timestamp(seconds, 0)?;
+            Some(match timezone() {
+                Some(zone) => datetime.with_timezone(&zone).date_naive(),
+                None => datetime.with_timezone(&Local).date_naive(),
+            })
+        }
+        ["next", unit] if parse_unit(unit).is_some() => offset(today, 1, parse_unit(unit)?),
+        ["last", unit] if parse_unit(unit).is_some() => offset(today, -1, parse_unit(unit)?),
+        ["next", day] => {
+            let weekday = parse_weekday(day)?;
+            let ahead = (7 + weekday.num_days_from_monday() as i64
+                - today.weekday().num_days_from_monday() as i64)
+                % 7;
+            Some(today + Days::new(if ahead == 0 { 7 } else { ahead as u64 }))
+        }
+        ["last", day] => {
+            let weekday = parse_weekday(day)?;
+            let behind = (7 + today.weekday().num_days_from_monday() as i64
+                - weekday.num_days_from_monday() as i64)
+                % 7;
+            Some(today - Days::new(if behind == 0 { 7 } else { behind as u64 }))
+        }
+        ["this", day] => {
+            // the given day of the current Monday-Sunday week
+            let weekday = parse_weekday(day)?;
+            let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+            Some(monday + Days::new(weekday.num_days_from_monday() as u64))
+        }
+        ["in", amount, unit] => offset(today, amount.parse().ok()?, parse_unit(unit)?),
+        [amount, unit, "ago"] => offset(
+            today,
+            amount.parse::<i64>().ok()?.checked_neg()?,
+            parse_unit(unit)?,
+        ),
+        _ => {
+            // +N, -N days, today+7, 2024-07-01-3 weeks
+            let compact: String = words.concat();
+            let split = compact
+                .char_indices()
+                .skip(1)
+                .filter(|(_, c)| *c == '+' || *c == '-')
+                .map(|(index, _)| index)
+                .find(|index| {
+                    let base = &compact[..*index];
+                    base.is_empty() || base_date(base, today).is_some()
+                })
+                .or_else(|| compact.starts_with(['+', '-']).then_some(0))?;
+            let base = if split == 0 {
+                today
+            } else {
+                base_date(&compact[..split], today)?
+            };
+            let (sign, rest) = compact[split..].split_at(1);
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            let unit = match &rest[digits.len()..] {
+                "" => Unit::Day,
+                unit => parse_unit(unit)?,
+            };
+            let amount: i64 = digits.parse().ok()?;
+            let amount = if sign == "-" {
+                amount.checked_neg()?
+            } else {
+                amount
+            };
+            offset(base, amount, unit)
+        }
+    }
+}
+
+enum Unit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+fn parse_unit(unit: &str) -> Option<Unit> {
+    match unit {
+        "d" | "day" | "days" => Some(Unit::Day),
+        "w" | "week" | "weeks" => Some(Unit::Week),
+        "m" | "month" | "months" => Some(Unit::Month),
+        "y" | "year" | "years" => Some(Unit::Year),
+        _ => None,
+    }
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    match day {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thur" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn base_date(base: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match base {
+        "today" => Some(today),
+        "tomorrow" => Some(today + Days::new(1)),
+        "yesterday" => Some(today - Days::new(1)),
+        _ => NaiveDate::parse_from_str(base, "%Y-%m-%d").ok(),
+    }
+}
+
+fn offset(date: NaiveDate, amount: i64, unit: Unit) -> Option<NaiveDate> {
+    let magnitude = amount.unsigned_abs();
+    match unit {
+        Unit::Day | Unit::Week => {
+            let days = Days::new(if let Unit::Week = unit {
+                magnitude.checked_mul(7)?
+            } else {
+                magnitude
+            });
+            if amount < 0 {
+                date.checked_sub_days(days)
+            } else {
+                date.checked_add_days(days)
+            }
+        }
+        Unit::Month | Unit::Year => {
+            let months = Months::new(
+                u32::try_from(if let Unit::Year = unit {
+                    magnitude.checked_mul(12)?
+                } else {
+                    magnitude
+                })
+                .ok()?,
+            );
+            if amount < 0 {
+                date.checked_sub_months(months)
+            } else {
+                date.checked_add_months(months)
+            }
+        }
+    }
+}
+
+/// Read one date per line from a file, or from stdin if the path is `-`, skipping blank
+/// lines and # comments.
+///
+/// Lines that aren't a valid date are skipped rather than failing, so one typo doesn't
+/// abort the whole batch; a warning naming each one by its line number is returned
+/// alongside the dates.
+pub fn read_dates(source: &str) -> Result<(Vec<String>, Vec<String>), Error> {
+    let mut contents = String::new();
+    let result = if source == "-" {
+        stdin().read_to_string(&mut contents).map(|_| ())
+    } else {
+        fs::read_to_string(source).map(|text| contents = text)
+    };
+    if let Err(err) = result {
+        return Err(Error::Io(format!(
+            "Unable to read dates from '{}': {}",
+            source, err
+        )));
+    }
+    let name = if source == "-" { "stdin" } else { source };
+    let mut dates = Vec::new();
+    let mut skipped = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // held to the same rules as a date given with --date, year 100 or later included
+        let date = resolve_date(line);
+        match try_parse_date(&date) {
+            Ok(_) => dates.push(date),
+            Err(err) => skipped.push(format!("{}:{}: {} Skipping it.", name, number + 1, err)),
+        }
+    }
+    Ok((dates, skipped))
+}
+
+/// Parse a date in YYYY-MM-DD format, or explain why it can't be used
+pub fn try_parse_date(date: &str) -> Result<NaiveDate, String> {
+    let naive_date = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(naive_date) => naive_date,
+        Err(err) if err.kind() == ParseErrorKind::OutOfRange => {
+            return Err(format!(
+                "Unable to parse date '{}'. Year, month or day value out of range.",
+                date
+            ));
+        }
+        Err(_) => {
+            let expected = match input_format() {
+                Some(input_format) => format!("YYYY-MM-DD, {}", input_format),
+                None => String::from("YYYY-MM-DD"),
+            };
+            return Err(format!(
+                "Unable to parse date '{}', expected {} or a relative date such as tomorrow.",
+                date, expected
+            ));
+        }
+    };
+    check_year(naive_date, date)
+}
+
+// the year is one rspotd can generate a password for
+fn check_year(date: NaiveDate, input: &str) -> Result<NaiveDate, String> {
+    rspotd_core::validate_date(date, input)?;
+    Ok(date)
+}
+
+/// First and last day of a month given as YYYY-MM
+pub fn month_range(month: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let unparsable = || format!("Unable to parse month '{}', expected YYYY-MM.", month);
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| unparsable())?;
+    let end = start
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(unparsable)?;
+    Ok((check_year(start, month)?, end))
+}
+
+/// First and last day of a year given as YYYY
+pub fn year_range(year: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let bounds = year.trim().parse().ok().and_then(|number| {
+        Some((
+            NaiveDate::from_ymd_opt(number, 1, 1)?,
+            NaiveDate::from_ymd_opt(number, 12, 31)?,
+        ))
+    });
+    let (start, end) =
+        bounds.ok_or_else(|| format!("Unable to parse year '{}', expected YYYY.", year))?;
+    Ok((check_year(start, year)?, end))
+}
+
+/// Monday and Sunday of an ISO 8601 week given as YYYY-Www, e.g. 2024-W27
+pub fn week_range(week: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let monday = week
+        .trim()
+        .to_uppercase()
+        .split_once("-W")
+        .and_then(|(year, number)| {
+            NaiveDate::from_isoywd_opt(year.parse().ok()?, number.parse().ok()?, Weekday::Mon)
+        })
+        .ok_or_else(|| {
+            format!(
+                "Unable to parse week '{}', expected an ISO week such as 2024-W27.",
+                week
+            )
+        })?;
+    Ok((
+        check_year(monday, week)?,
+        check_year(monday + Days::new(6), week)?,
+    ))
+}
+
+/// Which dates of a range end up in the output
+pub struct DateFilter {
+    /// Keep every Nth day, counting from the start of the range
+    pub step: u32,
+    /// Days of the week to keep; all of them if empty
+    pub weekdays: Vec<Weekday>,
+    /// Dates to leave out, such as holidays
+    pub exclude: Vec<NaiveDate>,
+}
+
+impl DateFilter {
+    pub fn keep(&self, start: NaiveDate, date: NaiveDate) -> bool {
+        (date - start).num_days() % i64::from(self.step) == 0
+            && (self.weekdays.is_empty() || self.weekdays.contains(&date.weekday()))
+            && !self.exclude.contains(&date)
+    }
+}
+
+/// Parse a day of the week given on the command line, e.g. mon or Friday
+pub fn weekday_arg(day: &str) -> Result<Weekday, String> {
+    parse_weekday(&day.trim().to_lowercase())
+        .ok_or_else(|| format!("'{}' is not a day of the week, e.g. mon or friday", day))
+}
@@ -0,0 +1,109 @@
+use crate::error::Error;
+use crate::format::Entry;
+use crate::seed::seed_fingerprint;
+use chrono::{NaiveDate, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OpenFlags};
+use std::path::Path;
+
+/// A password read back from the database
+pub struct Row {
+    pub date: String,
+    pub seed_id: String,
+    pub potd: String,
+}
+
+// one password per date and seed, the seed only by its fingerprint so the database doesn't
+// give away how to generate the rest
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS potd (
+    date TEXT NOT NULL,
+    seed_id TEXT NOT NULL,
+    password TEXT NOT NULL,
+    generated_at TEXT NOT NULL,
+    PRIMARY KEY (date, seed_id)
+)";
+
+/// Add the passwords to the database, creating it if needed and replacing any stored for the
+/// same date and seed
+pub fn store(file: &Path, entries: &[Entry]) -> Result<(), Error> {
+    // readable by its owner only, like --output files; SQLite gives its journal the same
+    #[cfg(unix)]
+    if !file.exists() {
+        use std::os::unix::fs::OpenOptionsExt;
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(file)
+            .map_err(|err| {
+                Error::Io(format!(
+                    "Unable to create database '{}': {}",
+                    file.display(),
+                    err
+                ))
+            })?;
+    }
+    let mut connection = Connection::open(file).map_err(|err| failed(file, err))?;
+    connection
+        .execute_batch(SCHEMA)
+        .map_err(|err| failed(file, err))?;
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    // a single transaction, so decades of passwords are one write rather than thousands
+    let transaction = connection.transaction().map_err(|err| failed(file, err))?;
+    {
+        let mut upsert = transaction
+            .prepare(
+                "INSERT INTO potd (date, seed_id, password, generated_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (date, seed_id) DO UPDATE
+                 SET password = excluded.password, generated_at = excluded.generated_at",
+            )
+            .map_err(|err| failed(file, err))?;
+        for entry in entries {
+            upsert
+                .execute(params![
+                    entry.date.format("%Y-%m-%d").to_string(),
+                    seed_fingerprint(&entry.seed),
+                    entry.potd,
+                    now
+                ])
+                .map_err(|err| failed(file, err))?;
+        }
+    }
+    transaction.commit().map_err(|err| failed(file, err))?;
+    Ok(())
+}
+
+/// The passwords stored for a date, for every seed or only the one with the given fingerprint
+pub fn query(file: &Path, date: NaiveDate, seed_id: Option<&str>) -> Result<Vec<Row>, Error> {
+    // a missing database is an error rather than a new empty one
+    let connection = Connection::open_with_flags(file, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| failed(file, err))?;
+    let mut select = connection
+        .prepare(
+            "SELECT date, seed_id, password FROM potd
+             WHERE date = ?1 AND (?2 IS NULL OR seed_id = ?2) ORDER BY seed_id",
+        )
+        .map_err(|err| failed(file, err))?;
+    let rows = select
+        .query_map(
+            params![date.format("%Y-%m-%d").to_string(), seed_id],
+            |row| {
+                Ok(Row {
+                    date: row.get(0)?,
+                    seed_id: row.get(1)?,
+                    potd: row.get(2)?,
+                })
+            },
+        )
+        .and_then(|rows| rows.collect::<Result<Vec<Row>, _>>())
+        .map_err(|err| failed(file, err))?;
+    Ok(rows)
+}
+
+fn failed(file: &Path, err: rusqlite::Error) -> Error {
+    Error::Io(format!(
+        "Unable to use database '{}': {}",
+        file.display(),
+        err
+    ))
+}
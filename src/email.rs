@@ -0,0 +1,77 @@
+use crate::config::EmailConfig;
+use crate::error::Error;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+use std::env;
+
+/// Mail the body to the given recipients, or the ones from the config file, failing if it
+/// can't be delivered
+pub fn send_email(
+    config: Option<&EmailConfig>,
+    to: &[String],
+    subject: &str,
+    body: &str,
+    html: bool,
+) -> Result<(), Error> {
+    let Some(config) = config else {
+        return Err(Error::Config(String::from(
+            "No [email] settings in the config file; see the Email section of the README.",
+        )));
+    };
+    let recipients = if to.is_empty() { &config.to } else { to };
+    if recipients.is_empty() {
+        return Err(Error::Usage(String::from(
+            "No recipients given; use --to or set 'to' in the [email] config.",
+        )));
+    }
+    let mut builder = Message::builder()
+        .from(mailbox(&config.from)?)
+        .subject(subject)
+        .header(if html {
+            ContentType::TEXT_HTML
+        } else {
+            ContentType::TEXT_PLAIN
+        });
+    for recipient in recipients {
+        builder = builder.to(mailbox(recipient)?);
+    }
+    let message = builder
+        .body(body.to_string())
+        .map_err(|err| Error::Failed(format!("Unable to build email: {}", err)))?;
+    let transport = match config.tls.as_str() {
+        "tls" => SmtpTransport::relay(&config.host),
+        "none" => Ok(SmtpTransport::builder_dangerous(&config.host)),
+        _ => SmtpTransport::starttls_relay(&config.host),
+    };
+    let mut transport = transport.map_err(|err| {
+        Error::Delivery(format!(
+            "Unable to connect to SMTP server '{}': {}",
+            config.host, err
+        ))
+    })?;
+    if let Some(port) = config.port {
+        transport = transport.port(port);
+    }
+    if let Some(username) = &config.username {
+        let password = env::var("RSPOTD_SMTP_PASSWORD")
+            .ok()
+            .or_else(|| config.password.clone())
+            .unwrap_or_default();
+        transport = transport.credentials(Credentials::new(username.to_string(), password));
+    }
+    transport.build().send(&message).map(|_| ()).map_err(|err| {
+        Error::Delivery(format!(
+            "Unable to send email through '{}': {}",
+            config.host, err
+        ))
+    })
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, Error> {
+    address
+        .parse()
+        .map_err(|err| Error::Usage(format!("Invalid email address '{}': {}", address, err)))
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+
+/// Why a run failed, each class with its own exit status so wrapper scripts can tell them
+/// apart
+pub enum Error {
+    /// Options that don't make sense together or values that can't be used; exits 2, like
+    /// the argument errors clap reports
+    Usage(String),
+    /// A date that can't be read, or a range that runs backwards; exits 3
+    InvalidDate(String),
+    /// A seed the generator won't take; exits 3
+    InvalidSeed(String),
+    /// A file, stdin or stdout that couldn't be read or written, or a port that couldn't be
+    /// listened on; exits 4
+    Io(String),
+    /// A config file that can't be read, parsed or updated; exits 5
+    Config(String),
+    /// Passwords that couldn't be sent where they were asked to go (email, chat, a webhook,
+    /// MQTT) or recorded with --log-sink; exits 6
+    Delivery(String),
+    /// Anything else; exits 1
+    Failed(String),
+    /// Whoever was reading stdout went away, e.g. `| head`, which isn't worth reporting;
+    /// exits 0
+    ClosedPipe,
+}
+
+impl Error {
+    /// The status to exit with
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::ClosedPipe => 0,
+            Error::Failed(_) => 1,
+            Error::Usage(_) => 2,
+            Error::InvalidDate(_) | Error::InvalidSeed(_) => 3,
+            Error::Io(_) => 4,
+            Error::Config(_) => 5,
+            Error::Delivery(_) => 6,
+        }
+    }
+
+    /// The class of failure, for --error-format json
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Usage(_) => "usage",
+            Error::InvalidDate(_) => "invalid_date",
+            Error::InvalidSeed(_) => "invalid_seed",
+            Error::Io(_) => "io",
+            Error::Config(_) => "config",
+            Error::Delivery(_) => "delivery",
+            Error::Failed(_) | Error::ClosedPipe => "other",
+        }
+    }
+
+    /// What to try next, for --error-format json
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Error::Usage(_) => Some("See --help for the options and the values they take."),
+            Error::InvalidDate(_) => Some(
+                "Dates are YYYY-MM-DD (or as set with --input-date-format), or relative, such as tomorrow or today+7.",
+            ),
+            Error::InvalidSeed(_) => Some(
+                "Seeds are 4-8 characters; check --seed, RSPOTD_SEED, the config file and the keyring.",
+            ),
+            Error::Config(_) => Some("Check the file given with --config, or the default config file."),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(message)
+            | Error::InvalidDate(message)
+            | Error::InvalidSeed(message)
+            | Error::Io(message)
+            | Error::Config(message)
+            | Error::Delivery(message)
+            | Error::Failed(message) => f.write_str(message),
+            Error::ClosedPipe => f.write_str("Output closed before it was finished."),
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Failed(message)
+    }
+}
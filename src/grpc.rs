@@ -0,0 +1,129 @@
+use crate::audit;
+use crate::dates::{resolve_date, today, try_parse_date};
+use crate::error::Error;
+use crate::potd::generate_range;
+use crate::seed::seed_fingerprint;
+use crate::serve::{bearer_matches, check_range};
+use chrono::NaiveDate;
+use proto::potd_server::{Potd, PotdServer};
+use proto::{GeneratePotdRequest, GenerateRangeRequest, Password, PasswordList};
+use std::net::SocketAddr;
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::{error, info};
+
+/// Code generated from proto/rspotd.proto
+pub mod proto {
+    tonic::include_proto!("rspotd.v1");
+}
+
+struct PotdService {
+    seeds: Vec<String>,
+}
+
+#[tonic::async_trait]
+impl Potd for PotdService {
+    async fn generate_potd(
+        &self,
+        request: Request<GeneratePotdRequest>,
+    ) -> Result<Response<PasswordList>, Status> {
+        let client = client(&request);
+        let date = request.into_inner().date;
+        let date = if date.is_empty() {
+            today()
+        } else {
+            request_date(&date)?
+        };
+        self.passwords(date, date, client.as_deref())
+    }
+
+    async fn generate_range(
+        &self,
+        request: Request<GenerateRangeRequest>,
+    ) -> Result<Response<PasswordList>, Status> {
+        let client = client(&request);
+        let request = request.into_inner();
+        let start = request_date(&request.start)?;
+        let end = request_date(&request.end)?;
+        check_range(start, end).map_err(Status::invalid_argument)?;
+        self.passwords(start, end, client.as_deref())
+    }
+}
+
+impl PotdService {
+    fn passwords(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        client: Option<&str>,
+    ) -> Result<Response<PasswordList>, Status> {
+        let mut passwords = Vec::new();
+        for seed in &self.seeds {
+            let generated = generate_range(start, end, seed)
+                .map_err(|err| Status::invalid_argument(err.to_string()))?;
+            for (date, potd) in generated {
+                if let Err(err) = audit::record("grpc", client, seed, date, &potd) {
+                    error!("{}", err);
+                    return Err(Status::internal(
+                        "Unable to record the request in the audit log.",
+                    ));
+                }
+                passwords.push(Password {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    password: potd,
+                    seed: if self.seeds.len() > 1 {
+                        seed_fingerprint(seed)
+                    } else {
+                        String::new()
+                    },
+                });
+            }
+        }
+        Ok(Response::new(PasswordList { passwords }))
+    }
+}
+
+fn client<T>(request: &Request<T>) -> Option<String> {
+    request
+        .remote_addr()
+        .map(|address| address.ip().to_string())
+}
+
+fn request_date(date: &str) -> Result<NaiveDate, Status> {
+    try_parse_date(&resolve_date(date)).map_err(Status::invalid_argument)
+}
+
+/// Answer gRPC requests for passwords until the process is stopped, requiring
+/// `authorization: Bearer <token>` metadata if a token is given
+pub fn serve_grpc(listen: &str, auth_token: Option<String>, seeds: &[String]) -> Result<(), Error> {
+    let address: SocketAddr = listen
+        .parse()
+        .map_err(|err| Error::Usage(format!("Invalid listen address '{}': {}", listen, err)))?;
+    let service = PotdService {
+        seeds: seeds.to_vec(),
+    };
+    let check_token = move |request: Request<()>| {
+        let Some(token) = &auth_token else {
+            return Ok(request);
+        };
+        let given = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if bearer_matches(given, token) {
+            Ok(request)
+        } else {
+            Err(Status::unauthenticated("Missing or invalid bearer token."))
+        }
+    };
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|err| Error::Failed(format!("Unable to start the gRPC server: {}", err)))?;
+    info!("Listening for gRPC on {}", address);
+    runtime
+        .block_on(
+            Server::builder()
+                .add_service(PotdServer::with_interceptor(service, check_token))
+                .serve(address),
+        )
+        .map_err(|err| Error::Io(format!("Unable to serve gRPC on {}: {}", listen, err)))
+}
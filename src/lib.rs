@@ -0,0 +1,46 @@
+//! The generation and formatting behind rspotd-cli, for tools that want to embed it rather
+//! than run the binary. [`potd::PotdIter`] generates a range lazily, and
+//! [`format::OutputWriter`] renders entries in any of the command line's output formats.
+//! [`run`] drives the whole command line with its own output streams.
+
+mod algorithm;
+mod audit;
+#[cfg(feature = "check")]
+mod check;
+mod cli;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod config;
+mod dates;
+#[cfg(feature = "db")]
+mod db;
+#[cfg(feature = "email")]
+mod email;
+mod error;
+#[cfg(feature = "grpc")]
+mod grpc;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "notify")]
+mod notify;
+mod output;
+mod plugin;
+pub mod potd;
+#[cfg(feature = "python")]
+mod python;
+mod repl;
+pub mod seed;
+mod serve;
+mod ssh;
+mod telnet;
+#[cfg(feature = "tui")]
+mod tui;
+mod watch;
+#[cfg(feature = "webhook")]
+mod webhook;
+#[cfg(feature = "login")]
+mod webui;
+mod wizard;
+
+pub use cli::run;
+pub use rspotd_core::format;
@@ -0,0 +1,244 @@
+use crate::dates::today;
+use crate::error::Error;
+use crate::watch::{watch, Pause};
+use chrono::NaiveDate;
+use clap::Args;
+use rumqttc::{Client, Connection, Event, MqttOptions, Outgoing, Packet, QoS};
+use std::{
+    collections::HashSet,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    thread::{sleep, spawn},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// how long to wait for the broker to acknowledge a publish before publishing again
+const ACK_TIMEOUT: Duration = Duration::from_secs(30);
+// how often to check for a stop request while waiting for one
+const STOP_CHECK: Duration = Duration::from_secs(1);
+// the wait before publishing again, doubled after every attempt up to the longest
+const FIRST_RETRY: Duration = Duration::from_secs(5);
+const LONGEST_RETRY: Duration = Duration::from_secs(300);
+// attempts with --once before giving up; otherwise a password is retried until it's
+// acknowledged or its day is over
+const ONCE_ATTEMPTS: u32 = 5;
+
+// what the event loop reports about publishes, by packet id
+enum Progress {
+    Sent(u16),
+    Acked(u16),
+}
+
+// matches the broker's acknowledgements to publishes by packet id, so a late one for an
+// earlier attempt or day isn't taken for the password being published now
+struct Acks {
+    progress: Receiver<Progress>,
+    // sent by earlier attempts and not acknowledged yet, which the event loop sends again
+    // after reconnecting
+    stale: HashSet<u16>,
+}
+
+impl Acks {
+    // wait for the broker to acknowledge one of the publishes in `sent`, adding any sent
+    // meanwhile to it; false as well if the watch is asked to stop first
+    fn wait(&mut self, sent: &mut HashSet<u16>, pause: Option<&Pause>) -> bool {
+        let deadline = Instant::now() + ACK_TIMEOUT;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            match self.progress.recv_timeout(left.min(STOP_CHECK)) {
+                Ok(Progress::Sent(pkid)) => {
+                    if !self.stale.contains(&pkid) {
+                        sent.insert(pkid);
+                    }
+                }
+                Ok(Progress::Acked(pkid)) => {
+                    if sent.remove(&pkid) {
+                        return true;
+                    }
+                    self.stale.remove(&pkid);
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if pause.is_some_and(Pause::stopped) {
+                        return false;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => return false,
+            }
+        }
+        false
+    }
+}
+
+/// Where and how to publish passwords over MQTT
+#[derive(Args)]
+pub struct MqttSettings {
+    #[arg(
+        short = 'b',
+        long = "broker",
+        help = "Broker to connect to as host or host:port; the port defaults to 1883"
+    )]
+    broker: String,
+
+    #[arg(short = 'T', long = "topic", help = "Topic to publish the password on")]
+    topic: String,
+
+    #[arg(
+        long = "retain",
+        help = "Publish as a retained message, so clients connecting later get the current password"
+    )]
+    retain: bool,
+
+    #[arg(
+        long = "client-id",
+        default_value = "rspotd-cli",
+        help = "Client identifier to connect with"
+    )]
+    client_id: String,
+
+    #[arg(
+        long = "username",
+        help = "User name to authenticate to the broker with"
+    )]
+    username: Option<String>,
+
+    #[arg(
+        long = "mqtt-password",
+        env = "RSPOTD_MQTT_PASSWORD",
+        hide_env_values = true,
+        requires = "username",
+        help = "Password to authenticate to the broker with"
+    )]
+    password: Option<String>,
+}
+
+/// Publish the payload for today's date, and then for every following day as the date rolls
+/// over unless `once` is set
+///
+/// A password the broker doesn't acknowledge is logged as a warning and published again, with a
+/// growing wait in between, until it is or its date has passed; with `once` only a few times.
+/// A payload that can't be produced stops publishing.
+pub fn publish(
+    settings: &MqttSettings,
+    once: bool,
+    mut payload: impl FnMut(NaiveDate) -> Result<String, Error>,
+) -> Result<(), Error> {
+    let (client, connection) = Client::new(mqtt_options(settings)?, 10);
+    let mut acks = Acks {
+        progress: drive(connection),
+        stale: HashSet::new(),
+    };
+    // `pause` is the watch's, which stops retrying when the process is asked to stop
+    let mut send = |date: NaiveDate, pause: Option<&Pause>| -> Result<bool, Error> {
+        let payload = payload(date)?;
+        let mut sent = HashSet::new();
+        let mut retry = FIRST_RETRY;
+        let mut attempts = 0;
+        let published = loop {
+            attempts += 1;
+            // try_publish rather than publish, which blocks while the broker is unreachable
+            // and the request queue is full
+            let result = client.try_publish(
+                settings.topic.as_str(),
+                QoS::AtLeastOnce,
+                settings.retain,
+                payload.as_str(),
+            );
+            match result {
+                Ok(()) if acks.wait(&mut sent, pause) => break true,
+                Ok(()) if pause.is_some_and(Pause::stopped) => break false,
+                Ok(()) => warn!(
+                    "The broker at {} didn't acknowledge the password for {}, retrying in {}s.",
+                    settings.broker,
+                    date,
+                    retry.as_secs()
+                ),
+                Err(err) => warn!(
+                    "Unable to publish to {}: {}, retrying in {}s.",
+                    settings.topic,
+                    err,
+                    retry.as_secs()
+                ),
+            }
+            if once && attempts == ONCE_ATTEMPTS {
+                break false;
+            }
+            match pause {
+                Some(pause) if !pause.sleep(retry) => break false,
+                Some(_) => {}
+                None => sleep(retry),
+            }
+            // publishing it now would replace the retained password with yesterday's
+            if today() > date {
+                warn!(
+                    "Giving up on the password for {}, the date has passed.",
+                    date
+                );
+                break false;
+            }
+            retry = (retry * 2).min(LONGEST_RETRY);
+        };
+        acks.stale.extend(sent);
+        if published {
+            info!("Published the password for {} to {}", date, settings.topic);
+        }
+        Ok(published)
+    };
+    if once {
+        let published = send(today(), None);
+        let _ = client.disconnect();
+        if !published? {
+            return Err(Error::Delivery(format!(
+                "Unable to publish to {}.",
+                settings.broker
+            )));
+        }
+        Ok(())
+    } else {
+        watch(|date, pause| send(date, Some(pause)).map(|_| ()))
+    }
+}
+
+fn mqtt_options(settings: &MqttSettings) -> Result<MqttOptions, Error> {
+    let broker = settings.broker.trim_start_matches("mqtt://");
+    let (host, port) = match broker.rsplit_once(':') {
+        Some((host, port)) => match port.parse() {
+            Ok(port) => (host, port),
+            Err(_) => {
+                return Err(Error::Usage(format!(
+                    "Invalid broker port in '{}'.",
+                    settings.broker
+                )))
+            }
+        },
+        None => (broker, 1883),
+    };
+    let mut options = MqttOptions::new(settings.client_id.as_str(), host, port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = &settings.username {
+        options.set_credentials(username, settings.password.as_deref().unwrap_or_default());
+    }
+    Ok(options)
+}
+
+// run the connection's event loop on its own thread, reconnecting after errors, and report
+// every publish as it is sent and as the broker acknowledges it
+fn drive(mut connection: Connection) -> Receiver<Progress> {
+    let (sender, receiver) = channel();
+    spawn(move || {
+        for event in connection.iter() {
+            match event {
+                Ok(Event::Outgoing(Outgoing::Publish(pkid))) => {
+                    let _ = sender.send(Progress::Sent(pkid));
+                }
+                Ok(Event::Incoming(Packet::PubAck(ack))) => {
+                    let _ = sender.send(Progress::Acked(ack.pkid));
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    warn!("MQTT connection error: {}", err);
+                    sleep(Duration::from_secs(5));
+                }
+            }
+        }
+    });
+    receiver
+}
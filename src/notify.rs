@@ -0,0 +1,145 @@
+use crate::error::Error;
+use crate::format::{format_date, html_escape, Entry, FormatOptions};
+use crate::seed::seed_fingerprint;
+use serde_json::json;
+use std::time::Duration;
+use ureq::Agent;
+
+/// A chat service to post the passwords to
+pub enum Service {
+    /// Slack incoming webhook URL
+    Slack(String),
+    /// Teams workflow or incoming webhook URL
+    Teams(String),
+    Telegram {
+        api_url: String,
+        token: String,
+        chat_id: String,
+    },
+}
+
+/// Post the passwords as a chat message, with the title in bold and the passwords in a code
+/// block, failing if the service doesn't accept it
+pub fn notify(
+    service: &Service,
+    title: &str,
+    options: &FormatOptions,
+    entries: &[Entry],
+) -> Result<(), Error> {
+    let lines = lines(options, entries);
+    let (url, payload) = match service {
+        Service::Slack(url) => (
+            url.clone(),
+            json!({ "text": format!("*{}*\n```\n{}\n```", slack_escape(title), slack_escape(&lines.join("\n"))) }),
+        ),
+        Service::Teams(url) => {
+            let mut body = vec![json!({
+                "type": "TextBlock",
+                "text": title,
+                "weight": "Bolder",
+                "size": "Medium",
+                "wrap": true
+            })];
+            body.extend(lines.iter().map(|line| {
+                json!({ "type": "TextBlock", "text": line, "fontType": "Monospace", "spacing": "Small" })
+            }));
+            (
+                url.clone(),
+                json!({
+                    "type": "message",
+                    "attachments": [{
+                        "contentType": "application/vnd.microsoft.card.adaptive",
+                        "content": {
+                            "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                            "type": "AdaptiveCard",
+                            "version": "1.4",
+                            "body": body
+                        }
+                    }]
+                }),
+            )
+        }
+        Service::Telegram {
+            api_url,
+            token,
+            chat_id,
+        } => (
+            format!("{}/bot{}/sendMessage", api_url.trim_end_matches('/'), token),
+            json!({
+                "chat_id": chat_id,
+                "parse_mode": "HTML",
+                "text": format!("<b>{}</b>\n<pre>{}</pre>", html_escape(title), html_escape(&lines.join("\n")))
+            }),
+        ),
+    };
+    let agent: Agent = Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(30)))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let response = agent
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .header(
+            "User-Agent",
+            concat!("rspotd-cli/", env!("CARGO_PKG_VERSION")),
+        )
+        .send(payload.to_string());
+    match response {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(mut response) => {
+            let status = response.status();
+            let reason = response.body_mut().read_to_string().unwrap_or_default();
+            Err(Error::Delivery(format!(
+                "{} rejected the message: {} {}",
+                service.name(),
+                status,
+                reason.trim()
+            )))
+        }
+        // the Telegram URL contains the bot token, so only name the service
+        Err(err) => Err(Error::Delivery(format!(
+            "Unable to reach {}: {}",
+            service.name(),
+            err
+        ))),
+    }
+}
+
+impl Service {
+    fn name(&self) -> &'static str {
+        match self {
+            Service::Slack(_) => "Slack",
+            Service::Teams(_) => "Teams",
+            Service::Telegram { .. } => "Telegram",
+        }
+    }
+}
+
+// just the password for a single date, otherwise one "date  password" line per entry,
+// prefixed with the seed's fingerprint when there are several
+fn lines(options: &FormatOptions, entries: &[Entry]) -> Vec<String> {
+    if entries.len() == 1 {
+        return vec![entries[0].potd.clone()];
+    }
+    let seeds = entries.iter().any(|entry| entry.seed != entries[0].seed);
+    entries
+        .iter()
+        .map(|entry| {
+            let line = format!("{}  {}", format_date(options, entry.date), entry.potd);
+            if seeds {
+                format!("{}  {}", seed_fingerprint(&entry.seed), line)
+            } else {
+                line
+            }
+        })
+        .collect()
+}
+
+// Slack only needs these three escaped, even inside code blocks
+fn slack_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
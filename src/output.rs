@@ -0,0 +1,394 @@
+use crate::error::Error;
+use chrono::Local;
+use std::{
+    fs::{self, File, OpenOptions, Permissions},
+    io::{self, BufWriter, Write},
+    path::{self, Path, PathBuf},
+    process,
+};
+
+/// How --output treats a file that's already there
+#[derive(Clone)]
+pub struct OutputOptions {
+    /// Add to the end rather than replace it
+    pub append: bool,
+    /// Replace it, which is refused otherwise unless it's backed up
+    pub force: bool,
+    /// Keep the replaced file under its name with this suffix added, date(1) specifiers
+    /// filled in from the current time
+    pub backup: Option<String>,
+    /// Permissions to give the file; otherwise a new file is readable by its owner only
+    /// and a replaced one keeps its own
+    pub mode: Option<u32>,
+    /// Create the directories leading up to it when they're missing
+    pub mkdir: bool,
+    /// gzip, zstd or none; taken from a .gz or .zst extension if not given
+    #[cfg(feature = "compress")]
+    pub compress: Option<String>,
+    /// age recipients to encrypt the file to; none leaves it unencrypted
+    #[cfg(feature = "encrypt")]
+    pub encrypt_to: Vec<String>,
+}
+
+/// The path an --output name refers to, a leading ~ standing for the home directory
+pub fn output_file(output: &str) -> PathBuf {
+    let home = output
+        .strip_prefix('~')
+        .filter(|rest| rest.is_empty() || rest.starts_with(path::is_separator))
+        .zip(dirs::home_dir());
+    match home {
+        Some((rest, home)) => home.join(rest.trim_start_matches(path::is_separator)),
+        None => PathBuf::from(output),
+    }
+}
+
+/// A file being written with --output. A new version goes to a temporary file next to the
+/// target and only replaces it once everything was written, so a crash or a full disk
+/// leaves the previous file rather than half a password sheet. Appending, and targets that
+/// aren't regular files such as symlinks and /dev/stdout, are written in place.
+pub struct OutputFile {
+    writer: Writer,
+    path: PathBuf,
+    // the temporary file, until it's renamed into place
+    temp: Option<Temp>,
+    // where the file being replaced goes, if it's kept
+    backup: Option<PathBuf>,
+}
+
+impl OutputFile {
+    pub fn create(path: &Path, options: &OutputOptions) -> Result<OutputFile, Error> {
+        let unable = || {
+            Error::Io(format!(
+                "Unable to create file '{}', likely due to issue with permissions.",
+                path.display()
+            ))
+        };
+        let directory = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty());
+        if let Some(directory) = directory.filter(|directory| !directory.exists()) {
+            if !options.mkdir {
+                return Err(Error::Io(format!(
+                    "Directory '{}' doesn't exist; create it or use --mkdir.",
+                    directory.display()
+                )));
+            }
+            fs::create_dir_all(directory).map_err(|err| {
+                Error::Io(format!(
+                    "Unable to create directory '{}': {}",
+                    directory.display(),
+                    err
+                ))
+            })?;
+        }
+        // following symlinks, as replacing a link to a file loses that file all the same
+        let replaces = fs::metadata(path).is_ok_and(|metadata| metadata.is_file());
+        if replaces && !options.append && !options.force && options.backup.is_none() {
+            return Err(Error::Usage(format!(
+                "'{}' already exists; use --force to overwrite it, --append to add to it or --backup to keep a copy.",
+                path.display()
+            )));
+        }
+        // not following symlinks, so a link such as /dev/stdout isn't replaced by a file
+        let existing = fs::symlink_metadata(path).ok();
+        let append = options.append;
+        if append
+            || existing
+                .as_ref()
+                .is_some_and(|metadata| !metadata.is_file())
+        {
+            let file = private(OpenOptions::new().append(append).create(true))
+                .write(true)
+                .truncate(!append)
+                .open(path)
+                .map_err(|_| unable())?;
+            // not touching devices such as /dev/stdout
+            if let Some(mode) = options
+                .mode
+                .filter(|_| existing.is_none_or(|metadata| metadata.is_file()))
+            {
+                set_mode(&file, mode).map_err(|err| mode_failed(path, err))?;
+            }
+            return Ok(OutputFile {
+                writer: Writer::new(file, path, options).map_err(|err| unable_to(path, err))?,
+                path: path.to_path_buf(),
+                temp: None,
+                backup: None,
+            });
+        }
+        let name = path.file_name().ok_or_else(unable)?.to_string_lossy();
+        let temp = Temp(path.with_file_name(format!(".{}.{}.tmp", name, process::id())));
+        let file = private(OpenOptions::new().create_new(true))
+            .write(true)
+            .open(&temp.0)
+            .map_err(|_| unable())?;
+        // the replacement keeps whatever permissions the file was given
+        match (options.mode, &existing) {
+            (Some(mode), _) => set_mode(&file, mode).map_err(|err| mode_failed(path, err))?,
+            (None, Some(metadata)) => {
+                let _ = file.set_permissions(metadata.permissions());
+            }
+            (None, None) => {}
+        }
+        #[cfg(windows)]
+        warn_if_public(path);
+        let backup = options
+            .backup
+            .as_ref()
+            .filter(|_| existing.is_some())
+            .map(|suffix| {
+                let suffix = Local::now().format(suffix).to_string();
+                path.with_file_name(format!("{}{}", name, suffix))
+            });
+        Ok(OutputFile {
+            writer: Writer::new(file, path, options).map_err(|err| unable_to(path, err))?,
+            path: path.to_path_buf(),
+            temp: Some(temp),
+            backup,
+        })
+    }
+
+    /// Flush what was written and, for a new version, put it in place of the target
+    pub fn commit(self) -> io::Result<()> {
+        let mut file = self.writer.finish()?;
+        file.flush()?;
+        let Some(temp) = self.temp else {
+            return Ok(());
+        };
+        file.get_ref().sync_all()?;
+        if let Some(backup) = &self.backup {
+            fs::rename(&self.path, backup).map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("unable to back it up to '{}': {}", backup.display(), err),
+                )
+            })?;
+        }
+        fs::rename(&temp.0, &self.path)
+    }
+}
+
+// a temporary file, removed if it isn't renamed into place, so anything that wasn't
+// committed is left out of the target
+struct Temp(PathBuf);
+
+impl Drop for Temp {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// the file, compressed as it's written if asked to
+enum Writer {
+    Plain(Sink),
+    #[cfg(feature = "compress")]
+    Gzip(flate2::write::GzEncoder<Sink>),
+    #[cfg(feature = "compress")]
+    Zstd(zstd::Encoder<'static, Sink>),
+}
+
+impl Writer {
+    #[cfg_attr(not(feature = "compress"), allow(unused_variables))]
+    fn new(file: File, path: &Path, options: &OutputOptions) -> io::Result<Writer> {
+        let file = Sink::new(BufWriter::new(file), options)?;
+        #[cfg(feature = "compress")]
+        {
+            // going by the name without .age, e.g. potd.csv.gz.age
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let name = name.strip_suffix(".age").unwrap_or(&name);
+            let compress = match options.compress.as_deref() {
+                Some(compress) => compress,
+                None if name.ends_with(".gz") => "gzip",
+                None if name.ends_with(".zst") => "zstd",
+                None => "none",
+            };
+            match compress {
+                "gzip" => {
+                    let level = flate2::Compression::default();
+                    return Ok(Writer::Gzip(flate2::write::GzEncoder::new(file, level)));
+                }
+                "zstd" => return Ok(Writer::Zstd(zstd::Encoder::new(file, 0)?)),
+                _ => {}
+            }
+        }
+        Ok(Writer::Plain(file))
+    }
+
+    // write out the end of the compressed stream, leaving the file itself to flush
+    fn finish(self) -> io::Result<BufWriter<File>> {
+        match self {
+            Writer::Plain(file) => file.finish(),
+            #[cfg(feature = "compress")]
+            Writer::Gzip(encoder) => encoder.finish()?.finish(),
+            #[cfg(feature = "compress")]
+            Writer::Zstd(encoder) => encoder.finish()?.finish(),
+        }
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Writer::Plain(file) => file.write(buf),
+            #[cfg(feature = "compress")]
+            Writer::Gzip(encoder) => encoder.write(buf),
+            #[cfg(feature = "compress")]
+            Writer::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Writer::Plain(file) => file.flush(),
+            #[cfg(feature = "compress")]
+            Writer::Gzip(encoder) => encoder.flush(),
+            #[cfg(feature = "compress")]
+            Writer::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+// the file, encrypted to --encrypt-to if given; compression comes before, as encrypted data
+// doesn't compress
+enum Sink {
+    File(BufWriter<File>),
+    #[cfg(feature = "encrypt")]
+    Age(age::stream::StreamWriter<BufWriter<File>>),
+}
+
+impl Sink {
+    #[cfg_attr(not(feature = "encrypt"), allow(unused_variables))]
+    fn new(file: BufWriter<File>, options: &OutputOptions) -> io::Result<Sink> {
+        #[cfg(feature = "encrypt")]
+        if !options.encrypt_to.is_empty() {
+            let recipients = options
+                .encrypt_to
+                .iter()
+                .map(|recipient| recipient_key(recipient).map_err(io::Error::other))
+                .collect::<io::Result<Vec<_>>>()?;
+            let encryptor = age::Encryptor::with_recipients(
+                recipients.iter().map(|recipient| recipient.as_ref()),
+            )
+            .map_err(io::Error::other)?;
+            return Ok(Sink::Age(encryptor.wrap_output(file)?));
+        }
+        Ok(Sink::File(file))
+    }
+
+    fn finish(self) -> io::Result<BufWriter<File>> {
+        match self {
+            Sink::File(file) => Ok(file),
+            #[cfg(feature = "encrypt")]
+            Sink::Age(stream) => stream.finish(),
+        }
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::File(file) => file.write(buf),
+            #[cfg(feature = "encrypt")]
+            Sink::Age(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::File(file) => file.flush(),
+            #[cfg(feature = "encrypt")]
+            Sink::Age(stream) => stream.flush(),
+        }
+    }
+}
+
+/// An age recipient to encrypt to: an age public key (age1...) or an SSH public key such as
+/// ssh-ed25519 AAAA...
+#[cfg(feature = "encrypt")]
+pub fn recipient_key(recipient: &str) -> Result<Box<dyn age::Recipient>, String> {
+    let recipient = recipient.trim();
+    if let Ok(key) = recipient.parse::<age::x25519::Recipient>() {
+        return Ok(Box::new(key));
+    }
+    match recipient.parse::<age::ssh::Recipient>() {
+        Ok(key) => Ok(Box::new(key)),
+        Err(_) => Err(format!(
+            "'{}' isn't an age public key (age1...) or a supported SSH public key",
+            recipient
+        )),
+    }
+}
+
+// a file created with these options is only readable and writable by its owner (rw-------),
+// as it holds passwords
+#[cfg(unix)]
+fn private(options: &mut OpenOptions) -> &mut OpenOptions {
+    use std::os::unix::fs::OpenOptionsExt;
+    options.mode(0o600)
+}
+
+// Windows gives a new file the permissions of its directory
+#[cfg(not(unix))]
+fn private(options: &mut OpenOptions) -> &mut OpenOptions {
+    options
+}
+
+#[cfg(unix)]
+fn set_mode(file: &File, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(Permissions::from_mode(mode))
+}
+
+// only the owner's write bit has an equivalent, the read-only attribute
+#[cfg(not(unix))]
+fn set_mode(file: &File, mode: u32) -> io::Result<()> {
+    let mut permissions: Permissions = file.metadata()?.permissions();
+    permissions.set_readonly(mode & 0o200 == 0);
+    file.set_permissions(permissions)
+}
+
+fn unable_to(path: &Path, err: io::Error) -> Error {
+    Error::Io(format!(
+        "Unable to write to file '{}': {}",
+        path.display(),
+        err
+    ))
+}
+
+fn mode_failed(path: &Path, err: io::Error) -> Error {
+    Error::Io(format!(
+        "Unable to set the permissions of file '{}': {}",
+        path.display(),
+        err
+    ))
+}
+
+// the Public folder is shared with every account on the machine
+#[cfg(windows)]
+fn warn_if_public(path: &Path) {
+    let Some(public) = std::env::var_os("PUBLIC").and_then(|public| fs::canonicalize(public).ok())
+    else {
+        return;
+    };
+    let directory = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty());
+    if fs::canonicalize(directory.unwrap_or(Path::new(".")))
+        .is_ok_and(|directory| directory.starts_with(&public))
+    {
+        tracing::warn!(
+            "'{}' is in the Public folder, where every user of this computer can read it",
+            path.display()
+        );
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,197 @@
+use crate::algorithm::PotdAlgorithm;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fs,
+    io::{Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::OnceLock,
+    thread::{self, sleep},
+    time::{Duration, Instant},
+};
+
+// looked for once, the first time an algorithm is looked up by name
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+// how long a plugin has to answer before it's killed
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An algorithm provided by an executable in the plugins directory, named after the file.
+///
+/// The executable is run with a request on stdin, `{"seed": "...", "dates": ["2024-04-21",
+/// ...]}`, and answers on stdout with the passwords in the same order, `{"passwords": [...]}`,
+/// or with `{"error": "..."}` if it can't generate them. An empty list of dates asks only
+/// whether the seed is valid, and an empty seed means the plugin's own default.
+pub struct Plugin {
+    name: &'static str,
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    passwords: Vec<String>,
+    error: Option<String>,
+}
+
+/// Where plugins are looked for, e.g. ~/.config/rspotd/plugins on Linux, next to the config
+/// file
+pub fn plugins_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("rspotd").join("plugins"))
+}
+
+/// The executables in the plugins directory, none if it doesn't exist
+pub fn plugins() -> &'static [Plugin] {
+    PLUGINS.get_or_init(|| {
+        let Some(entries) = plugins_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut plugins: Vec<Plugin> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| executable(path))
+            .filter_map(|path| {
+                let name = path.file_stem()?.to_str()?.to_string();
+                (!name.starts_with('.')).then(|| Plugin {
+                    // lives for the rest of the run, like the list itself
+                    name: Box::leak(name.into_boxed_str()),
+                    path,
+                })
+            })
+            .collect();
+        plugins.sort_by_key(|plugin| plugin.name);
+        plugins
+    })
+}
+
+#[cfg(unix)]
+fn executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn executable(path: &std::path::Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("exe"))
+}
+
+impl Plugin {
+    // run the plugin for the dates, failing with what it said if it couldn't generate them
+    fn run(&self, seed: &str, dates: &[NaiveDate]) -> Result<Vec<String>, String> {
+        let dates: Vec<String> = dates
+            .iter()
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .collect();
+        let request = serde_json::json!({ "seed": seed, "dates": dates });
+        let failed = |err: String| format!("Plugin '{}' failed: {}", self.name, err);
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| failed(err.to_string()))?;
+        // written and read on their own threads, so a plugin that answers before reading all of
+        // a long request, or writes a lot to stderr, can't leave both sides waiting on a full
+        // pipe; one that doesn't read its request closes stdin early, which its answer explains
+        let mut stdin = child.stdin.take();
+        thread::spawn(move || {
+            if let Some(stdin) = &mut stdin {
+                let _ = writeln!(stdin, "{}", request);
+            }
+        });
+        let stdout = read_all(child.stdout.take());
+        let stderr = read_all(child.stderr.take());
+        let deadline = Instant::now() + TIMEOUT;
+        let status = loop {
+            match child.try_wait().map_err(|err| failed(err.to_string()))? {
+                Some(status) => break status,
+                None if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(failed(String::from("timed out")));
+                }
+                None => sleep(Duration::from_millis(10)),
+            }
+        };
+        // the pipes close with the plugin, unless it left a child of its own holding them open
+        while !(stdout.is_finished() && stderr.is_finished()) {
+            if Instant::now() >= deadline {
+                return Err(failed(String::from("timed out")));
+            }
+            sleep(Duration::from_millis(10));
+        }
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        let response: Option<Response> = serde_json::from_slice(&stdout).ok();
+        match response {
+            Some(Response {
+                error: Some(error), ..
+            }) => Err(error),
+            Some(response) if status.success() => {
+                if response.passwords.len() != dates.len() {
+                    return Err(failed(format!(
+                        "it answered with {} passwords for {} dates",
+                        response.passwords.len(),
+                        dates.len()
+                    )));
+                }
+                Ok(response.passwords)
+            }
+            _ => {
+                let stderr = String::from_utf8_lossy(&stderr);
+                Err(failed(match stderr.trim() {
+                    "" if status.success() => String::from("it didn't answer with JSON"),
+                    "" => status.to_string(),
+                    stderr => stderr.to_string(),
+                }))
+            }
+        }
+    }
+}
+
+// everything written to one of a plugin's pipes, read on a thread of its own
+fn read_all(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut contents = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut contents);
+        }
+        contents
+    })
+}
+
+impl PotdAlgorithm for Plugin {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn default_seed(&self) -> &'static str {
+        ""
+    }
+
+    fn validate_seed(&self, seed: &str) -> Result<(), String> {
+        self.run(seed, &[]).map(|_| ())
+    }
+
+    fn generate(&self, date: NaiveDate, seed: &str) -> Result<String, Box<dyn Error>> {
+        Ok(self.run(seed, &[date])?.remove(0))
+    }
+
+    // a single run for the whole range rather than one per date
+    fn generate_range(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        seed: &str,
+    ) -> Result<Vec<(NaiveDate, String)>, Box<dyn Error>> {
+        let dates: Vec<NaiveDate> = start.iter_days().take_while(|date| *date <= end).collect();
+        let passwords = self.run(seed, &dates)?;
+        Ok(dates.into_iter().zip(passwords).collect())
+    }
+}
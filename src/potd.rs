@@ -0,0 +1,158 @@
+use crate::algorithm;
+use chrono::NaiveDate;
+use rayon::prelude::*;
+use rspotd_core::range::{chunk_end, chunks};
+use std::error::Error;
+
+/// Generate the password for every date from start to end, inclusive.
+///
+/// The range is generated in chunks of up to a year, which the algorithm can generate faster
+/// than a date at a time. Chunks are generated in parallel and put back together in date
+/// order.
+pub fn generate_range(
+    start: NaiveDate,
+    end: NaiveDate,
+    seed: &str,
+) -> Result<Vec<(NaiveDate, String)>, Box<dyn Error>> {
+    generate_range_with_progress(start, end, seed, |_| {})
+}
+
+/// Like `generate_range`, calling `progress` with the number of dates generated after each chunk
+pub fn generate_range_with_progress(
+    start: NaiveDate,
+    end: NaiveDate,
+    seed: &str,
+    progress: impl Fn(u64) + Sync,
+) -> Result<Vec<(NaiveDate, String)>, Box<dyn Error>> {
+    let generated: Result<Vec<Vec<(NaiveDate, String)>>, String> = chunks(start, end)
+        .into_par_iter()
+        .map(|(chunk_start, chunk_end)| {
+            // boxed errors can't cross threads, so they travel as their message
+            let passwords =
+                generate_chunk(chunk_start, chunk_end, seed).map_err(|err| err.to_string())?;
+            progress(passwords.len() as u64);
+            Ok(passwords)
+        })
+        .collect();
+    Ok(generated?.into_iter().flatten().collect())
+}
+
+/// The password for every date from start to end, inclusive, generated a year at a time as
+/// the iterator is consumed, so a range of any length only ever holds a year of passwords
+pub struct PotdIter {
+    seed: String,
+    next: Option<NaiveDate>,
+    end: NaiveDate,
+    chunk: std::vec::IntoIter<(NaiveDate, String)>,
+}
+
+impl PotdIter {
+    /// Fails if the seed is invalid or either date's year doesn't fit in the four digits the
+    /// generator expects; an end before the start gives an empty iterator
+    pub fn new(start: NaiveDate, end: NaiveDate, seed: &str) -> Result<PotdIter, Box<dyn Error>> {
+        // every date in between is valid if both ends are
+        generate(&start.format("%Y-%m-%d").to_string(), seed)?;
+        generate(&end.format("%Y-%m-%d").to_string(), seed)?;
+        Ok(PotdIter {
+            seed: seed.to_string(),
+            next: Some(start).filter(|start| *start <= end),
+            end,
+            chunk: Vec::new().into_iter(),
+        })
+    }
+}
+
+impl Iterator for PotdIter {
+    type Item = (NaiveDate, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(password) = self.chunk.next() {
+            return Some(password);
+        }
+        let start = self.next?;
+        let end = chunk_end(start, self.end);
+        let passwords = generate_chunk(start, end, &self.seed)
+            .expect("the seed and range were checked when the iterator was created");
+        self.next = end.succ_opt().filter(|next| *next <= self.end);
+        self.chunk = passwords.into_iter();
+        self.chunk.next()
+    }
+}
+
+/// The password for a date in YYYY-MM-DD form, with the algorithm selected with --algorithm
+pub fn generate(date: &str, seed: &str) -> Result<String, Box<dyn Error>> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+    algorithm::selected().generate(date, seed)
+}
+
+fn generate_chunk(
+    start: NaiveDate,
+    end: NaiveDate,
+    seed: &str,
+) -> Result<Vec<(NaiveDate, String)>, Box<dyn Error>> {
+    algorithm::selected().generate_range(start, end, seed)
+}
+
+/// The intermediate values the selected algorithm goes through for a date, as label and
+/// value pairs in calculation order ending with the password, for checking it by hand
+pub fn steps(date: NaiveDate, seed: &str) -> Result<Vec<(&'static str, String)>, Box<dyn Error>> {
+    algorithm::selected().steps(date, seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn single_day() {
+        let day = date("2024-04-21");
+        let passwords: Vec<_> = PotdIter::new(day, day, "ASDF").unwrap().collect();
+        assert_eq!(passwords, vec![(day, String::from("08GY8HS1RH"))]);
+    }
+
+    #[test]
+    fn end_before_start() {
+        let mut passwords = PotdIter::new(date("2024-04-22"), date("2024-04-21"), "ASDF").unwrap();
+        assert_eq!(passwords.next(), None);
+    }
+
+    #[test]
+    fn chunks_join_up() {
+        // a chunk is 366 dates, so this spans three of them with a partial last one
+        let (start, end) = (date("2023-01-01"), date("2025-03-31"));
+        let passwords: Vec<_> = PotdIter::new(start, end, "ASDF").unwrap().collect();
+        let dates: Vec<NaiveDate> = start.iter_days().take_while(|day| *day <= end).collect();
+        assert_eq!(passwords.len(), dates.len());
+        for ((day, potd), expected) in passwords.iter().zip(&dates) {
+            assert_eq!(day, expected);
+            let single = generate(&day.format("%Y-%m-%d").to_string(), "ASDF").unwrap();
+            assert_eq!(*potd, single, "{}", day);
+        }
+        assert_eq!(passwords, generate_range(start, end, "ASDF").unwrap());
+    }
+
+    #[test]
+    fn chunk_boundary() {
+        // exactly one chunk, and one date past it
+        let start = date("2024-01-01");
+        let last = start + Duration::days(365);
+        assert_eq!(PotdIter::new(start, last, "ASDF").unwrap().count(), 366);
+        let passwords: Vec<_> = PotdIter::new(start, last + Duration::days(1), "ASDF")
+            .unwrap()
+            .collect();
+        assert_eq!(passwords.len(), 367);
+        let day = last + Duration::days(1);
+        let single = generate(&day.format("%Y-%m-%d").to_string(), "ASDF").unwrap();
+        assert_eq!(passwords[366], (day, single));
+    }
+
+    #[test]
+    fn invalid_seed() {
+        assert!(PotdIter::new(date("2024-04-21"), date("2024-04-22"), "AB").is_err());
+    }
+}
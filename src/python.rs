@@ -0,0 +1,123 @@
+use crate::algorithm::{self, PotdAlgorithm};
+use crate::dates::{current_date, resolve_date, try_parse_date};
+use chrono::NaiveDate;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+create_exception!(
+    rspotd_cli,
+    InvalidDateError,
+    PyValueError,
+    "A date that can't be parsed or is out of range"
+);
+create_exception!(
+    rspotd_cli,
+    InvalidSeedError,
+    PyValueError,
+    "A seed the algorithm can't use"
+);
+create_exception!(
+    rspotd_cli,
+    GenerationError,
+    PyRuntimeError,
+    "An algorithm or plugin that failed to generate a password"
+);
+
+/// The `rspotd_cli` Python module, built with maturin. Dates are parsed and seeds checked as on
+/// the command line, and the exceptions carry the same messages.
+#[pymodule]
+fn rspotd_cli(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(generate, module)?)?;
+    module.add_function(wrap_pyfunction!(generate_range, module)?)?;
+    module.add_function(wrap_pyfunction!(verify, module)?)?;
+    module.add(
+        "InvalidDateError",
+        module.py().get_type::<InvalidDateError>(),
+    )?;
+    module.add(
+        "InvalidSeedError",
+        module.py().get_type::<InvalidSeedError>(),
+    )?;
+    module.add("GenerationError", module.py().get_type::<GenerationError>())?;
+    Ok(())
+}
+
+/// The password for a date, today's if not given, e.g. generate("2024-04-21", seed="ASDF")
+#[pyfunction]
+#[pyo3(signature = (date=None, seed=None, algorithm=None))]
+fn generate(date: Option<&str>, seed: Option<&str>, algorithm: Option<&str>) -> PyResult<String> {
+    let algorithm = pick(algorithm)?;
+    let seed = checked_seed(algorithm, seed)?;
+    let date = parse_date(date)?;
+    algorithm
+        .generate(date, seed)
+        .map_err(|err| GenerationError::new_err(err.to_string()))
+}
+
+/// The (date, password) pairs for every date from start to end, inclusive, with dates in
+/// YYYY-MM-DD form
+#[pyfunction]
+#[pyo3(signature = (start, end, seed=None, algorithm=None))]
+fn generate_range(
+    start: &str,
+    end: &str,
+    seed: Option<&str>,
+    algorithm: Option<&str>,
+) -> PyResult<Vec<(String, String)>> {
+    let algorithm = pick(algorithm)?;
+    let seed = checked_seed(algorithm, seed)?;
+    let (start, end) = (parse_date(Some(start))?, parse_date(Some(end))?);
+    if end < start {
+        return Err(InvalidDateError::new_err(
+            "Invalid date range. Beginning date must not occur after end date.",
+        ));
+    }
+    let passwords = algorithm
+        .generate_range(start, end, seed)
+        .map_err(|err| GenerationError::new_err(err.to_string()))?;
+    Ok(passwords
+        .into_iter()
+        .map(|(date, potd)| (date.format("%Y-%m-%d").to_string(), potd))
+        .collect())
+}
+
+/// Whether the password is the one for the date, today if not given, ignoring case as `verify`
+/// on the command line does
+#[pyfunction]
+#[pyo3(signature = (password, date=None, seed=None, algorithm=None))]
+fn verify(
+    password: &str,
+    date: Option<&str>,
+    seed: Option<&str>,
+    algorithm: Option<&str>,
+) -> PyResult<bool> {
+    let potd = generate(date, seed, algorithm)?;
+    Ok(potd.eq_ignore_ascii_case(password.trim()))
+}
+
+// the named algorithm, including plugins, or ARRIS
+fn pick(name: Option<&str>) -> PyResult<&'static dyn PotdAlgorithm> {
+    let name = name.unwrap_or("arris");
+    algorithm::by_name(name).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "Unknown algorithm '{}', must be one of: {}.",
+            name,
+            algorithm::names().join(", ")
+        ))
+    })
+}
+
+fn checked_seed<'a>(algorithm: &dyn PotdAlgorithm, seed: Option<&'a str>) -> PyResult<&'a str> {
+    let seed = seed.unwrap_or(algorithm.default_seed());
+    algorithm
+        .validate_seed(seed)
+        .map_err(InvalidSeedError::new_err)?;
+    Ok(seed)
+}
+
+// relative dates such as tomorrow are taken as they are on the command line
+fn parse_date(date: Option<&str>) -> PyResult<NaiveDate> {
+    let date = date.map(resolve_date).unwrap_or_else(current_date);
+    try_parse_date(&date).map_err(InvalidDateError::new_err)
+}
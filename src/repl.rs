@@ -0,0 +1,96 @@
+use crate::audit;
+use crate::dates::{month_range, resolve_date, try_parse_date, week_range, year_range};
+use crate::format::{format_potd, format_potd_range, Entry, FormatOptions};
+use crate::potd::generate_range;
+use chrono::NaiveDate;
+use std::io::{stdin, stdout, BufRead, IsTerminal, Write};
+
+const HELP: &str = "\
+DATE                 password for a date, e.g. 2024-07-01, tomorrow or next monday
+range START END      passwords from START to END, e.g. range today today+6
+month YYYY-MM        passwords for a month
+week YYYY-Www        passwords for an ISO week
+year YYYY            passwords for a year
+help                 show this help
+quit                 leave (or press Ctrl-D)";
+
+/// Read dates and commands from stdin and print their passwords until input ends or the user
+/// quits, using the same seeds and formatting throughout
+pub fn run(options: &FormatOptions, seeds: &[String]) {
+    let interactive = stdin().is_terminal();
+    if interactive {
+        println!("Type a date, or 'help' for more commands.");
+    }
+    let mut lines = stdin().lock().lines();
+    loop {
+        if interactive {
+            print!("potd> ");
+            let _ = stdout().flush();
+        }
+        let Some(Ok(line)) = lines.next() else {
+            if interactive {
+                println!();
+            }
+            return;
+        };
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let range = match command {
+            "" => continue,
+            "help" | "?" => {
+                println!("{}", HELP);
+                continue;
+            }
+            "quit" | "exit" => return,
+            "range" => match rest.split_whitespace().collect::<Vec<&str>>()[..] {
+                [start, end] => parse(start).and_then(|start| Ok((start, parse(end)?))),
+                _ => Err(String::from(
+                    "range takes a start and end date, e.g. range today today+6",
+                )),
+            },
+            "month" => month_range(rest),
+            "week" => week_range(rest),
+            "year" => year_range(rest),
+            _ => parse(line).map(|date| (date, date)),
+        };
+        let output = range
+            .and_then(|(start, end)| passwords(seeds, start, end))
+            .and_then(|entries| {
+                if entries.len() == seeds.len() {
+                    format_potd(options, &entries)
+                } else {
+                    format_potd_range(options, &entries)
+                }
+            });
+        match output {
+            Ok(output) | Err(output) => println!("{}", output),
+        }
+    }
+}
+
+fn parse(date: &str) -> Result<NaiveDate, String> {
+    try_parse_date(&resolve_date(date))
+}
+
+// every seed's passwords for the range, recorded with --log-sink before they are shown
+fn passwords(seeds: &[String], start: NaiveDate, end: NaiveDate) -> Result<Vec<Entry>, String> {
+    if end < start {
+        return Err(String::from(
+            "Invalid date range. Beginning date must not occur after end date.",
+        ));
+    }
+    // the same checks as for a typed date, e.g. that the year isn't too small to generate for
+    try_parse_date(&start.format("%Y-%m-%d").to_string())?;
+    let mut entries = Vec::new();
+    for seed in seeds {
+        for (date, potd) in generate_range(start, end, seed).map_err(|err| err.to_string())? {
+            audit::record("repl", None, seed, date, &potd)?;
+            entries.push(Entry {
+                seed: seed.to_string(),
+                date,
+                potd,
+            });
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,122 @@
+use crate::algorithm;
+use rand::Rng;
+use rspotd::vals::ALPHANUM;
+pub use rspotd_core::seed_fingerprint;
+use std::{
+    fs,
+    io::{stdin, BufRead, IsTerminal},
+};
+
+/// Ask for the seed on the terminal with echo disabled
+pub fn prompt_seed() -> Result<String, String> {
+    rpassword::prompt_password("Seed: ").map_err(|err| format!("Unable to read seed: {}", err))
+}
+
+/// Read the seed from stdin, prompting without echo if stdin is a terminal
+pub fn read_seed() -> Result<String, String> {
+    if stdin().is_terminal() {
+        return prompt_seed();
+    }
+    let mut line = String::new();
+    if let Err(err) = stdin().lock().read_line(&mut line) {
+        return Err(format!("Unable to read seed from stdin: {}", err));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Read the seed from the first line of a file, ignoring surrounding whitespace
+pub fn read_seed_file(path: &str) -> Result<String, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read seed file '{}': {}", path, err))?;
+    let seed = contents.lines().next().unwrap_or("").trim();
+    if seed.is_empty() {
+        return Err(format!("Seed file '{}' is empty.", path));
+    }
+    Ok(seed.to_string())
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new("rspotd", "seed")
+        .map_err(|err| format!("Unable to access the OS keyring: {}", err))
+}
+
+/// Save the seed in the OS keyring
+#[cfg(feature = "keyring")]
+pub fn store_keyring_seed(seed: &str) -> Result<(), String> {
+    keyring_entry()?
+        .set_password(seed)
+        .map_err(|err| format!("Unable to store seed in the OS keyring: {}", err))
+}
+
+/// Fetch the seed previously saved with `seed store`
+#[cfg(feature = "keyring")]
+pub fn keyring_seed() -> Result<String, String> {
+    match keyring_entry()?.get_password() {
+        Ok(seed) => Ok(seed),
+        Err(keyring::Error::NoEntry) => Err(String::from(
+            "No seed found in the OS keyring; save one with 'seed store' first.",
+        )),
+        Err(err) => Err(format!("Unable to read seed from the OS keyring: {}", err)),
+    }
+}
+
+/// Remove the seed from the OS keyring
+#[cfg(feature = "keyring")]
+pub fn forget_keyring_seed() -> Result<(), String> {
+    match keyring_entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(format!(
+            "Unable to remove seed from the OS keyring: {}",
+            err
+        )),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn store_keyring_seed(_seed: &str) -> Result<(), String> {
+    Err(keyring_unsupported())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_seed() -> Result<String, String> {
+    Err(keyring_unsupported())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn forget_keyring_seed() -> Result<(), String> {
+    Err(keyring_unsupported())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_unsupported() -> String {
+    String::from("This build of rspotd-cli does not include OS keyring support.")
+}
+
+/// Check a seed against the constraints of the algorithm selected with --algorithm,
+/// explaining exactly what is wrong and how to fix it so the library's generic error is never
+/// reached
+pub fn validate_seed(seed: &str) -> Result<(), String> {
+    algorithm::selected().validate_seed(seed)
+}
+
+/// Generate a random seed of the given length from the same character set the passwords use
+pub fn random_seed(length: usize) -> String {
+    // ThreadRng is a CSPRNG seeded from the operating system
+    let mut rng = rand::rng();
+    (0..length)
+        .map(|_| ALPHANUM[rng.random_range(0..ALPHANUM.len())])
+        .collect()
+}
+
+/// Read one seed per line from a file, skipping blank lines and # comments
+pub fn read_seeds_file(path: &str) -> Result<Vec<String>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read seeds file '{}': {}", path, err))?;
+    Ok(contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect())
+}
@@ -0,0 +1,304 @@
+use crate::audit;
+use crate::dates::{next_midnight, resolve_date, today, try_parse_date};
+use crate::error::Error;
+use crate::potd::generate_range;
+use crate::seed::seed_fingerprint;
+use chrono::{NaiveDate, Utc};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{debug, error, info, warn};
+
+// the longest range a single request may ask for, matching the official tooling
+const MAX_RANGE_DAYS: i64 = 366;
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Settings for the HTTP server
+pub struct ServeOptions {
+    pub listen: String,
+    /// Require `Authorization: Bearer <token>` on every request
+    pub auth_token: Option<String>,
+    /// Requests allowed per client address per minute; 0 for no limit
+    pub rate_limit: u32,
+}
+
+// requests seen from each client address in the current window
+struct RateLimiter {
+    limit: u32,
+    clients: HashMap<IpAddr, (Instant, u32)>,
+}
+
+impl RateLimiter {
+    // count a request, returning the seconds until the client may retry if it's over the limit
+    fn check(&mut self, client: IpAddr) -> Option<u64> {
+        if self.limit == 0 {
+            return None;
+        }
+        let now = Instant::now();
+        self.clients
+            .retain(|_, (start, _)| now.duration_since(*start) < RATE_WINDOW);
+        let (start, count) = self.clients.entry(client).or_insert((now, 0));
+        *count += 1;
+        if *count > self.limit {
+            let retry = RATE_WINDOW.saturating_sub(now.duration_since(*start));
+            Some(retry.as_secs().max(1))
+        } else {
+            None
+        }
+    }
+}
+
+/// Answer HTTP requests for passwords until the process is stopped:
+///
+/// - `GET /potd?date=DATE` returns today's password, or the one for the given date
+/// - `GET /potd/range?start=DATE&end=DATE` returns the passwords for a range of dates
+/// - `GET /metrics` returns request counters and the time until the next rotation for
+///   Prometheus
+///
+/// Dates are accepted in the same forms as on the command line, and passwords are returned
+/// as `{"date": "...", "password": "..."}` objects, with a `seed` key when there are
+/// several seeds.
+pub fn serve(options: &ServeOptions, seeds: &[String]) -> Result<(), Error> {
+    let server = Server::http(&options.listen)
+        .map_err(|err| Error::Io(format!("Unable to listen on {}: {}", options.listen, err)))?;
+    let loopback = server
+        .server_addr()
+        .to_ip()
+        .is_some_and(|address| address.ip().is_loopback());
+    if options.auth_token.is_none() && !loopback {
+        warn!(
+            "Passwords are served to anyone who can reach {} without --auth-token.",
+            options.listen
+        );
+    }
+    info!("Listening on http://{}", options.listen);
+    let mut limiter = RateLimiter {
+        limit: options.rate_limit,
+        clients: HashMap::new(),
+    };
+    let mut metrics = Metrics::default();
+    for request in server.incoming_requests() {
+        let client = request.remote_addr().map(SocketAddr::ip);
+        let retry_after = client.and_then(|client| limiter.check(client));
+        let path = request.url().split('?').next().unwrap_or_default();
+        let mut headers = vec![json_header()];
+        let (status, body) = if let Some(seconds) = retry_after {
+            headers.push(header("Retry-After", &seconds.to_string()));
+            error(429, "Too many requests.")
+        } else if path == "/metrics" {
+            // metrics don't reveal any passwords, so scrapers don't need the token
+            headers = vec![header("Content-Type", "text/plain; version=0.0.4")];
+            (200, metrics.render())
+        } else if !authorized(&request, options.auth_token.as_deref()) {
+            headers.push(header("WWW-Authenticate", "Bearer"));
+            error(401, "Missing or invalid bearer token.")
+        } else {
+            respond(&request, seeds)
+        };
+        metrics.record(path, status);
+        debug!(
+            client = client.map(|client| client.to_string()),
+            method = %request.method(),
+            path,
+            status,
+            "Answered request"
+        );
+        let mut response = Response::from_string(body).with_status_code(status);
+        for header in headers {
+            response.add_header(header);
+        }
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+// request and error counters exposed on /metrics in the Prometheus text format
+#[derive(Default)]
+struct Metrics {
+    requests: BTreeMap<(&'static str, u16), u64>,
+    errors: BTreeMap<&'static str, u64>,
+}
+
+impl Metrics {
+    fn record(&mut self, path: &str, status: u16) {
+        // unknown paths are lumped together to keep the number of series bounded
+        let path = ["/potd", "/potd/range", "/metrics"]
+            .into_iter()
+            .find(|known| *known == path)
+            .unwrap_or("other");
+        *self.requests.entry((path, status)).or_default() += 1;
+        if status >= 400 {
+            *self.errors.entry(path).or_default() += 1;
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut lines = vec![
+            String::from(
+                "# HELP rspotd_http_requests_total HTTP requests by path and status code.",
+            ),
+            String::from("# TYPE rspotd_http_requests_total counter"),
+        ];
+        for ((path, status), count) in &self.requests {
+            lines.push(format!(
+                "rspotd_http_requests_total{{path=\"{}\",code=\"{}\"}} {}",
+                path, status, count
+            ));
+        }
+        lines.push(String::from(
+            "# HELP rspotd_http_errors_total HTTP requests answered with an error, by path.",
+        ));
+        lines.push(String::from("# TYPE rspotd_http_errors_total counter"));
+        for (path, count) in &self.errors {
+            lines.push(format!(
+                "rspotd_http_errors_total{{path=\"{}\"}} {}",
+                path, count
+            ));
+        }
+        let remaining = (next_midnight() - Utc::now()).num_milliseconds() as f64 / 1000.0;
+        lines.push(String::from("# HELP rspotd_seconds_until_rotation Seconds until the date rolls over and a new password is served."));
+        lines.push(String::from("# TYPE rspotd_seconds_until_rotation gauge"));
+        lines.push(format!("rspotd_seconds_until_rotation {}", remaining));
+        lines.push(String::new());
+        lines.join("\n")
+    }
+}
+
+fn authorized(request: &Request, token: Option<&str>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+    let given = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .map(|header| header.value.as_str())
+        .unwrap_or_default();
+    bearer_matches(given, token)
+}
+
+/// Whether an Authorization header value carries the given bearer token, compared without
+/// bailing out at the first differing byte so response times don't give it away
+pub fn bearer_matches(authorization: &str, token: &str) -> bool {
+    let given = authorization.strip_prefix("Bearer ").unwrap_or_default();
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).unwrap()
+}
+
+fn json_header() -> Header {
+    header("Content-Type", "application/json")
+}
+
+fn respond(request: &Request, seeds: &[String]) -> (u16, String) {
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let query: HashMap<String, String> = form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+    if *request.method() != Method::Get {
+        return error(405, "Only GET requests are supported.");
+    }
+    let client = request
+        .remote_addr()
+        .map(|address| address.ip().to_string());
+    let client = client.as_deref();
+    let result = match path {
+        "/potd" => query_date(&query, "date")
+            .map_err(bad_request)
+            .and_then(|date| {
+                let date = date.unwrap_or_else(today);
+                let mut passwords = passwords(seeds, date, date, client)?;
+                Ok(if passwords.len() == 1 {
+                    passwords.remove(0)
+                } else {
+                    Value::Array(passwords)
+                })
+            }),
+        "/potd/range" => query_range(&query)
+            .map_err(bad_request)
+            .and_then(|(start, end)| passwords(seeds, start, end, client).map(Value::Array)),
+        _ => return error(404, "Not found."),
+    };
+    match result {
+        Ok(body) => (200, body.to_string()),
+        Err(response) => response,
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, String) {
+    (status, json!({ "error": message }).to_string())
+}
+
+fn bad_request(message: String) -> (u16, String) {
+    error(400, &message)
+}
+
+fn query_date(query: &HashMap<String, String>, name: &str) -> Result<Option<NaiveDate>, String> {
+    match query.get(name) {
+        Some(date) => try_parse_date(&resolve_date(date)).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn query_range(query: &HashMap<String, String>) -> Result<(NaiveDate, NaiveDate), String> {
+    let start = query_date(query, "start")?.ok_or("Missing start date.")?;
+    let end = query_date(query, "end")?.ok_or("Missing end date.")?;
+    check_range(start, end)?;
+    Ok((start, end))
+}
+
+/// Reject ranges that end before they start or are too long to serve in one response
+pub fn check_range(start: NaiveDate, end: NaiveDate) -> Result<(), String> {
+    if end < start {
+        return Err(String::from(
+            "Invalid date range. Beginning date must not occur after end date.",
+        ));
+    }
+    if (end - start).num_days() >= MAX_RANGE_DAYS {
+        return Err(format!(
+            "Invalid date range. At most {} days can be requested at once.",
+            MAX_RANGE_DAYS
+        ));
+    }
+    Ok(())
+}
+
+// {"date": ..., "password": ...} for every date, with a leading "seed" key holding the seed's
+// fingerprint when there are several seeds; each one is recorded with --log-sink first
+fn passwords(
+    seeds: &[String],
+    start: NaiveDate,
+    end: NaiveDate,
+    client: Option<&str>,
+) -> Result<Vec<Value>, (u16, String)> {
+    let mut passwords = Vec::new();
+    for seed in seeds {
+        let generated =
+            generate_range(start, end, seed).map_err(|err| error(400, &err.to_string()))?;
+        for (date, potd) in generated {
+            if let Err(err) = audit::record("http", client, seed, date, &potd) {
+                error!("{}", err);
+                return Err(error(500, "Unable to record the request in the audit log."));
+            }
+            let date = date.format("%Y-%m-%d").to_string();
+            passwords.push(if seeds.len() > 1 {
+                json!({ "seed": seed_fingerprint(seed), "date": date, "password": potd })
+            } else {
+                json!({ "date": date, "password": potd })
+            });
+        }
+    }
+    Ok(passwords)
+}
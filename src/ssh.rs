@@ -0,0 +1,90 @@
+use crate::error::Error;
+use std::{
+    env,
+    ffi::OsStr,
+    io::{self, Write},
+    process::{Command, ExitCode},
+};
+
+/// Holds the password for the askpass program, which is this binary run again by ssh
+pub const ASKPASS_VARIABLE: &str = "RSPOTD_SSH_PASSWORD";
+
+/// Where to log in and what to run there
+pub struct SshOptions {
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    /// Run instead of an interactive shell, if given
+    pub command: Vec<String>,
+}
+
+/// Run ssh against the host, answering its password prompt with the password through
+/// SSH_ASKPASS, and exit as ssh does. Needs OpenSSH 8.4 or later for SSH_ASKPASS_REQUIRE.
+pub fn ssh(options: &SshOptions, password: &str) -> Result<ExitCode, Error> {
+    let program = env::current_exe().map_err(|err| {
+        Error::Io(format!(
+            "Unable to find rspotd-cli to answer ssh's prompts: {}",
+            err
+        ))
+    })?;
+    let mut ssh = Command::new("ssh");
+    ssh.env("SSH_ASKPASS", program)
+        .env("SSH_ASKPASS_REQUIRE", "force")
+        .env(ASKPASS_VARIABLE, password)
+        // the password before any keys, which a device may count as failed logins
+        .args([
+            "-o",
+            "PreferredAuthentications=keyboard-interactive,password",
+        ]);
+    if let Some(user) = &options.user {
+        ssh.args(["-l", user]);
+    }
+    if let Some(port) = options.port {
+        ssh.args(["-p", &port.to_string()]);
+    }
+    ssh.arg(&options.host).args(&options.command);
+    let status = ssh
+        .status()
+        .map_err(|err| Error::Io(format!("Unable to run ssh: {}", err)))?;
+    // killed by a signal if there's no code
+    Ok(status
+        .code()
+        .map_or(ExitCode::FAILURE, |code| ExitCode::from(code as u8)))
+}
+
+/// Answer a prompt from ssh as its askpass program: the password for a password prompt, and
+/// whatever is typed at the terminal for anything else, such as whether to trust a new host
+/// key
+pub fn askpass(password: &OsStr, prompt: Option<&OsStr>, mut out: impl Write) -> ExitCode {
+    let prompt = prompt.map(OsStr::to_string_lossy).unwrap_or_default();
+    let answer = if prompt.to_lowercase().contains("password") {
+        Ok(password.to_string_lossy().into_owned())
+    } else {
+        ask_terminal(&prompt)
+    };
+    match answer.and_then(|answer| writeln!(out, "{}", answer)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(unix)]
+fn ask_terminal(prompt: &str) -> io::Result<String> {
+    use std::{
+        fs::OpenOptions,
+        io::{BufRead, BufReader},
+    };
+    let mut terminal = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    write!(terminal, "{}", prompt)?;
+    let mut answer = String::new();
+    BufReader::new(terminal).read_line(&mut answer)?;
+    Ok(answer.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(not(unix))]
+fn ask_terminal(_prompt: &str) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "only password prompts can be answered",
+    ))
+}
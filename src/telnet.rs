@@ -0,0 +1,262 @@
+use std::{
+    io::{self, BufRead, Read, Write},
+    net::{Shutdown, TcpStream, ToSocketAddrs},
+    thread,
+    time::{Duration, Instant},
+};
+
+// the telnet commands and options that matter for a login
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+
+// prompts, answers to a password and shell prompts, matched ignoring case
+const USER_PROMPTS: [&str; 3] = ["login:", "username:", "user name:"];
+const PASSWORD_PROMPT: &str = "password:";
+const REJECTED: [&str; 5] = ["incorrect", "invalid", "denied", "failed", "bad password"];
+const SHELL_PROMPTS: [&str; 3] = ["#", "$", ">"];
+
+/// How a device answered a password
+pub enum Login {
+    Accepted,
+    /// With what the device said
+    Rejected(String),
+    /// Neither accepted nor rejected before the timeout, e.g. at a prompt that isn't
+    /// recognised
+    Unanswered,
+}
+
+// where the parser is within the telnet commands mixed into the data
+enum State {
+    Data,
+    Command,
+    Option(u8),
+    Subnegotiation,
+    SubnegotiationCommand,
+}
+
+/// A telnet connection with an expect-style engine: wait for a prompt, then answer it
+pub struct Telnet {
+    stream: TcpStream,
+    // text received and not yet matched by `expect`
+    received: String,
+    state: State,
+    // whether to show what's received on stdout
+    echo: bool,
+}
+
+impl Telnet {
+    pub fn connect(host: &str, port: u16, timeout: Duration) -> io::Result<Self> {
+        let address = (host, port).to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no address for {}", host))
+        })?;
+        let stream = TcpStream::connect_timeout(&address, timeout)?;
+        Ok(Telnet {
+            stream,
+            received: String::new(),
+            state: State::Data,
+            echo: false,
+        })
+    }
+
+    /// Wait for any of `patterns`, ignoring case, and return which one came first along with
+    /// the text up to the end of it
+    pub fn expect(&mut self, patterns: &[&str], timeout: Duration) -> io::Result<(usize, String)> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let received = self.received.to_ascii_lowercase();
+            let found = patterns
+                .iter()
+                .enumerate()
+                .filter_map(|(index, pattern)| {
+                    let at = received.find(&pattern.to_ascii_lowercase())?;
+                    Some((index, at + pattern.len()))
+                })
+                .min_by_key(|(_, end)| *end);
+            if let Some((index, end)) = found {
+                return Ok((index, self.received.drain(..end).collect()));
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+            }
+            self.stream.set_read_timeout(Some(left))?;
+            let mut buffer = [0; 4096];
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "the device closed the connection",
+                    ))
+                }
+                Ok(read) => self.receive(&buffer[..read])?,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Send a line of input, as if typed and followed by Enter
+    pub fn send_line(&mut self, line: &str) -> io::Result<()> {
+        self.stream.write_all(&encode_line(line))
+    }
+
+    /// Answer the login and password prompts, and tell whether the device let us in
+    pub fn login(&mut self, user: &str, password: &str, timeout: Duration) -> io::Result<Login> {
+        let prompts: Vec<&str> = USER_PROMPTS
+            .iter()
+            .copied()
+            .chain([PASSWORD_PROMPT])
+            .collect();
+        // some devices only ask for a password
+        let (prompt, _) = self
+            .expect(&prompts, timeout)
+            .map_err(|err| waiting_for("a login prompt", err))?;
+        if prompt < USER_PROMPTS.len() {
+            self.send_line(user)?;
+            self.expect(&[PASSWORD_PROMPT], timeout)
+                .map_err(|err| waiting_for("the password prompt", err))?;
+        }
+        self.send_line(password)?;
+        // asked again for a login or password means the last one didn't work
+        let answers: Vec<&str> = REJECTED
+            .iter()
+            .chain(&USER_PROMPTS)
+            .chain(&[PASSWORD_PROMPT])
+            .chain(&SHELL_PROMPTS)
+            .copied()
+            .collect();
+        let (answer, text) = match self.expect(&answers, timeout) {
+            Ok(answer) => answer,
+            Err(err) if err.kind() == io::ErrorKind::TimedOut => return Ok(Login::Unanswered),
+            Err(err) => return Err(waiting_for("an answer to the password", err)),
+        };
+        if answer >= answers.len() - SHELL_PROMPTS.len() {
+            return Ok(Login::Accepted);
+        }
+        let said = text
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .unwrap_or_default();
+        Ok(Login::Rejected(said.to_string()))
+    }
+
+    /// Log in, showing everything the device sends, and unless the password is rejected hand
+    /// the session over to the terminal a line at a time until either side closes it
+    pub fn interact(mut self, user: &str, password: &str, timeout: Duration) -> io::Result<Login> {
+        self.echo = true;
+        let login = self.login(user, password, timeout)?;
+        if let Login::Rejected(_) = login {
+            // the device's next prompt is left without a newline
+            println!();
+            return Ok(login);
+        }
+        self.received.clear();
+        let mut input = self.stream.try_clone()?;
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if input.write_all(&encode_line(&line)).is_err() {
+                    return;
+                }
+            }
+            // end of input, e.g. Ctrl-D, ends the session
+            let _ = input.shutdown(Shutdown::Write);
+        });
+        self.stream.set_read_timeout(None)?;
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => break,
+                // some devices reset the connection rather than close it when logging out
+                Err(err) if err.kind() == io::ErrorKind::ConnectionReset => break,
+                Ok(read) => {
+                    self.receive(&buffer[..read])?;
+                    self.received.clear();
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(login)
+    }
+
+    // keep the data and answer the option negotiation: the device may leave out go-aheads,
+    // and gets a refusal for anything else, including echoing, since input is sent a line at
+    // a time after the terminal has echoed it
+    fn receive(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let mut data = Vec::with_capacity(bytes.len());
+        let mut replies = Vec::new();
+        for &byte in bytes {
+            self.state = match (&self.state, byte) {
+                (State::Data, IAC) => State::Command,
+                (State::Data, _) => {
+                    data.push(byte);
+                    State::Data
+                }
+                (State::Command, IAC) => {
+                    data.push(IAC);
+                    State::Data
+                }
+                (State::Command, DO | DONT | WILL | WONT) => State::Option(byte),
+                (State::Command, SB) => State::Subnegotiation,
+                (State::Command, _) => State::Data,
+                (State::Option(command), option) => {
+                    let reply = match (*command, option) {
+                        (WILL, SUPPRESS_GO_AHEAD) => Some(DO),
+                        (WILL, _) => Some(DONT),
+                        (DO, SUPPRESS_GO_AHEAD) => Some(WILL),
+                        (DO, _) => Some(WONT),
+                        _ => None,
+                    };
+                    if let Some(reply) = reply {
+                        replies.extend_from_slice(&[IAC, reply, option]);
+                    }
+                    State::Data
+                }
+                (State::Subnegotiation, IAC) => State::SubnegotiationCommand,
+                (State::Subnegotiation, _) => State::Subnegotiation,
+                (State::SubnegotiationCommand, SE) => State::Data,
+                (State::SubnegotiationCommand, _) => State::Subnegotiation,
+            };
+        }
+        if self.echo {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(&data)?;
+            stdout.flush()?;
+        }
+        self.received.push_str(&String::from_utf8_lossy(&data));
+        if !replies.is_empty() {
+            self.stream.write_all(&replies)?;
+        }
+        Ok(())
+    }
+}
+
+// a line as typed and followed by Enter, with any literal 255 doubled so it isn't read as a
+// command
+fn encode_line(line: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(line.len() + 2);
+    for byte in line.bytes() {
+        if byte == IAC {
+            bytes.push(IAC);
+        }
+        bytes.push(byte);
+    }
+    bytes.extend_from_slice(b"\r\n");
+    bytes
+}
+
+fn waiting_for(what: &str, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{} while waiting for {}", err, what))
+}
@@ -0,0 +1,228 @@
+use crate::audit;
+use crate::dates::today;
+use crate::format::{format_date, localized_date, Entry, FormatOptions};
+use crate::potd::generate;
+use crate::seed::seed_fingerprint;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    DefaultTerminal, Frame,
+};
+use std::{io, time::Duration};
+
+const HELP: &str = "←→↑↓ day/week  PgUp/PgDn month  t today  c copy  q quit";
+
+struct App<'a> {
+    options: &'a FormatOptions,
+    seeds: &'a [String],
+    selected: NaiveDate,
+    // the passwords for the selected date, or why they can't be shown
+    passwords: Result<Vec<Entry>, String>,
+    status: String,
+    #[cfg(feature = "clipboard")]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+/// Browse passwords on a month calendar until the user quits
+pub fn run(options: &FormatOptions, seeds: &[String]) -> Result<(), String> {
+    use std::io::IsTerminal;
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(String::from("The tui needs an interactive terminal."));
+    }
+    let mut terminal =
+        ratatui::try_init().map_err(|err| format!("Unable to start the tui: {}", err))?;
+    let mut app = App {
+        options,
+        seeds,
+        selected: today(),
+        passwords: Ok(Vec::new()),
+        status: String::new(),
+        #[cfg(feature = "clipboard")]
+        clipboard: None,
+    };
+    app.select(today());
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result.map_err(|err| format!("Unable to run the tui: {}", err))
+}
+
+impl App<'_> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            // wake up now and then so the today marker moves at midnight
+            if !event::poll(Duration::from_secs(1))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let selected = self.selected;
+            let date = match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(())
+                }
+                KeyCode::Char('c') => {
+                    self.copy();
+                    continue;
+                }
+                KeyCode::Left | KeyCode::Char('h') => selected.checked_sub_days(Days::new(1)),
+                KeyCode::Right | KeyCode::Char('l') => selected.checked_add_days(Days::new(1)),
+                KeyCode::Up | KeyCode::Char('k') => selected.checked_sub_days(Days::new(7)),
+                KeyCode::Down | KeyCode::Char('j') => selected.checked_add_days(Days::new(7)),
+                KeyCode::PageUp | KeyCode::Char('[') => selected.checked_sub_months(Months::new(1)),
+                KeyCode::PageDown | KeyCode::Char(']') => {
+                    selected.checked_add_months(Months::new(1))
+                }
+                KeyCode::Home | KeyCode::Char('t') => Some(today()),
+                _ => None,
+            };
+            // the generator only handles four-digit years
+            if let Some(date) = date.filter(|date| (100..=9999).contains(&date.year())) {
+                self.select(date);
+            }
+        }
+    }
+
+    fn select(&mut self, date: NaiveDate) {
+        self.selected = date;
+        self.status.clear();
+        let day = date.format("%Y-%m-%d").to_string();
+        let mut entries = Vec::new();
+        for seed in self.seeds {
+            let potd = match generate(&day, seed) {
+                Ok(potd) => potd,
+                Err(err) => {
+                    self.passwords = Err(err.to_string());
+                    return;
+                }
+            };
+            // nothing is shown unless --log-sink could record it
+            if let Err(err) = audit::record("tui", None, seed, date, &potd) {
+                self.passwords = Err(err);
+                return;
+            }
+            entries.push(Entry {
+                seed: seed.to_string(),
+                date,
+                potd,
+            });
+        }
+        self.passwords = Ok(entries);
+    }
+
+    #[cfg(feature = "clipboard")]
+    fn copy(&mut self) {
+        let Ok(entries) = &self.passwords else {
+            return;
+        };
+        let passwords: Vec<&str> = entries.iter().map(|entry| entry.potd.as_str()).collect();
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(err) => {
+                    self.status = format!("Unable to open the clipboard: {}", err);
+                    return;
+                }
+            }
+        }
+        let clipboard = self.clipboard.as_mut().unwrap();
+        self.status = match crate::clipboard::set(clipboard, &passwords.join("\n")) {
+            Ok(()) => String::from("Copied to the clipboard."),
+            Err(err) => format!("Unable to copy to the clipboard: {}", err),
+        };
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn copy(&mut self) {
+        self.status = String::from("This build has no clipboard support.");
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, status, help] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [calendar, details] =
+            Layout::horizontal([Constraint::Length(24), Constraint::Min(0)]).areas(main);
+        frame.render_widget(
+            Paragraph::new(self.calendar()).block(Block::bordered().title(Line::from(
+                localized_date(self.selected, " %B %Y ", self.options.locale),
+            ))),
+            calendar,
+        );
+        frame.render_widget(
+            Paragraph::new(self.details()).block(Block::bordered().title(" Password of the day ")),
+            details,
+        );
+        frame.render_widget(Paragraph::new(self.status.as_str()), status);
+        frame.render_widget(Paragraph::new(HELP).dim(), help);
+    }
+
+    // the selected month, weeks starting on Monday, with the selected day highlighted and
+    // today underlined
+    fn calendar(&self) -> Vec<Line<'static>> {
+        let first = self.selected.with_day(1).unwrap();
+        let mut lines = vec![Line::from(" Mo Tu We Th Fr Sa Su").bold()];
+        let mut week = vec![Span::raw(
+            "   ".repeat(first.weekday().num_days_from_monday() as usize),
+        )];
+        let today = today();
+        for date in first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+        {
+            let mut style = Style::default();
+            if date == today {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            if date == self.selected {
+                style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            week.push(Span::raw(" "));
+            week.push(Span::styled(format!("{:>2}", date.day()), style));
+            if date.weekday().num_days_from_monday() == 6 {
+                lines.push(Line::from(std::mem::take(&mut week)));
+            }
+        }
+        if !week.is_empty() {
+            lines.push(Line::from(week));
+        }
+        lines
+    }
+
+    fn details(&self) -> Vec<Line<'_>> {
+        let mut lines = vec![
+            Line::from(format_date(self.options, self.selected)).bold(),
+            Line::from(localized_date(self.selected, "%A", self.options.locale)).dim(),
+            Line::default(),
+        ];
+        match &self.passwords {
+            Ok(entries) => {
+                for entry in entries {
+                    let password = Span::raw(entry.potd.clone()).bold().reversed();
+                    lines.push(if self.seeds.len() > 1 {
+                        Line::from(vec![
+                            Span::raw(format!("{}  ", seed_fingerprint(&entry.seed))),
+                            password,
+                        ])
+                    } else {
+                        Line::from(password)
+                    });
+                }
+            }
+            Err(err) => lines.push(Line::from(err.as_str()).red()),
+        }
+        lines
+    }
+}
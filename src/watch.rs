@@ -0,0 +1,200 @@
+use crate::dates::{next_midnight, today};
+use chrono::{NaiveDate, Utc};
+use std::{
+    cell::Cell,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+#[cfg(feature = "mqtt")]
+use std::time::Instant;
+use tracing::{debug, info, warn};
+
+// wake up at least this often to notice clock changes, e.g. after a suspend or NTP step
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Call `rotate` with today's date, then again every time the date rolls over, until the
+/// process is asked to stop with SIGTERM or SIGINT, or `rotate` fails. Midnight is taken in the time zone set
+/// with --timezone, or the local one, including across daylight saving time changes.
+///
+/// `rotate` is given a [`Pause`] to wait with between attempts, so a stop request isn't held
+/// up by it.
+///
+/// When run as a systemd service with `Type=notify`, readiness, the current date and
+/// watchdog keep-alives are reported to the service manager.
+pub fn watch<E>(mut rotate: impl FnMut(NaiveDate, &Pause) -> Result<(), E>) -> Result<(), E> {
+    let pause = Pause {
+        stop: stop_signal(),
+        watchdog: watchdog_interval(),
+        stopping: Cell::new(false),
+    };
+    let mut current = today();
+    info!("Watching for date changes, starting with {}", current);
+    rotate(current, &pause)?;
+    notify_ready(current);
+    loop {
+        let remaining = (next_midnight() - Utc::now()).to_std().unwrap_or_default();
+        let mut wait = remaining.min(MAX_SLEEP);
+        if let Some(interval) = pause.watchdog {
+            wait = wait.min(interval);
+        }
+        debug!(
+            "Next rotation in {}s, waking up in {}s",
+            remaining.as_secs(),
+            wait.as_secs()
+        );
+        if !pause.wait(wait) {
+            info!("Stopping");
+            break;
+        }
+        notify_watchdog();
+        let date = today();
+        if date != current {
+            info!("Date changed to {}", date);
+            current = date;
+            if let Err(err) = rotate(current, &pause) {
+                notify_stopping();
+                return Err(err);
+            }
+            notify_status(current);
+        }
+    }
+    notify_stopping();
+    Ok(())
+}
+
+/// Waits on behalf of [`watch`]'s `rotate`, cut short when the process is asked to stop
+pub struct Pause {
+    stop: Receiver<()>,
+    watchdog: Option<Duration>,
+    // asked to stop while `rotate` was waiting, which `watch` has yet to act on
+    stopping: Cell<bool>,
+}
+
+impl Pause {
+    /// Wait for the given time, keeping the systemd watchdog fed meanwhile; false if the
+    /// process was asked to stop first
+    #[cfg(feature = "mqtt")]
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return true;
+            }
+            if !self.wait(self.watchdog.map_or(left, |interval| left.min(interval))) {
+                return false;
+            }
+            notify_watchdog();
+        }
+    }
+
+    /// Whether the process has been asked to stop, for checking while busy rather than waiting;
+    /// keeps the systemd watchdog fed like [`Pause::sleep`]
+    #[cfg(feature = "mqtt")]
+    pub fn stopped(&self) -> bool {
+        notify_watchdog();
+        !self.wait(Duration::ZERO)
+    }
+
+    // false if asked to stop before the time is up
+    fn wait(&self, duration: Duration) -> bool {
+        if self.stopping.get() {
+            return false;
+        }
+        match self.stop.recv_timeout(duration) {
+            Err(RecvTimeoutError::Timeout) => true,
+            _ => {
+                self.stopping.set(true);
+                false
+            }
+        }
+    }
+}
+
+// a channel that receives a message once SIGTERM or SIGINT arrives, so the wait between
+// rotations can be cut short
+#[cfg(unix)]
+fn stop_signal() -> Receiver<()> {
+    use signal_hook::{
+        consts::{SIGINT, SIGTERM},
+        iterator::Signals,
+    };
+    let (sender, receiver) = channel();
+    match Signals::new([SIGTERM, SIGINT]) {
+        Ok(mut signals) => {
+            std::thread::spawn(move || {
+                if signals.forever().next().is_some() {
+                    let _ = sender.send(());
+                }
+            });
+        }
+        Err(err) => {
+            warn!("Unable to handle SIGTERM: {}", err);
+            // keep the channel open so waiting still times out
+            std::mem::forget(sender);
+        }
+    }
+    receiver
+}
+
+#[cfg(not(unix))]
+fn stop_signal() -> Receiver<()> {
+    let (sender, receiver) = channel();
+    std::mem::forget(sender);
+    receiver
+}
+
+// systemd expects a keep-alive at least this often when WatchdogSec= is set; ping at half
+// the interval to leave some slack
+#[cfg(unix)]
+fn watchdog_interval() -> Option<Duration> {
+    sd_notify::watchdog_enabled().map(|interval| interval / 2)
+}
+
+#[cfg(not(unix))]
+fn watchdog_interval() -> Option<Duration> {
+    None
+}
+
+// all notifications are no-ops unless started by systemd with NOTIFY_SOCKET set
+#[cfg(unix)]
+fn notify_ready(date: NaiveDate) {
+    let status = status(date);
+    let _ = sd_notify::notify(&[
+        sd_notify::NotifyState::Ready,
+        sd_notify::NotifyState::Status(&status),
+    ]);
+}
+
+#[cfg(unix)]
+fn notify_status(date: NaiveDate) {
+    let status = status(date);
+    let _ = sd_notify::notify(&[sd_notify::NotifyState::Status(&status)]);
+}
+
+#[cfg(unix)]
+fn notify_watchdog() {
+    let _ = sd_notify::notify(&[sd_notify::NotifyState::Watchdog]);
+}
+
+#[cfg(unix)]
+fn notify_stopping() {
+    let _ = sd_notify::notify(&[sd_notify::NotifyState::Stopping]);
+}
+
+#[cfg(unix)]
+fn status(date: NaiveDate) -> String {
+    format!("Password for {} is current", date.format("%Y-%m-%d"))
+}
+
+#[cfg(not(unix))]
+fn notify_ready(_date: NaiveDate) {}
+
+#[cfg(not(unix))]
+fn notify_status(_date: NaiveDate) {}
+
+#[cfg(not(unix))]
+fn notify_watchdog() {}
+
+#[cfg(not(unix))]
+fn notify_stopping() {}
@@ -0,0 +1,72 @@
+use crate::error::Error;
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use std::{thread::sleep, time::Duration};
+use tracing::warn;
+use ureq::Agent;
+
+/// Where and how to deliver generated passwords over HTTP
+pub struct Webhook {
+    pub url: String,
+    /// Key for the X-Rspotd-Signature header, so the receiver can check the payload is genuine
+    pub secret: Option<String>,
+    /// Further attempts after a failed delivery
+    pub retries: u32,
+}
+
+/// POST a JSON payload to the webhook, retrying with exponential backoff on connection
+/// errors, 429 and 5xx responses
+pub fn deliver(webhook: &Webhook, body: &str) -> Result<(), Error> {
+    let agent: Agent = Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(30)))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let mut attempt = 0;
+    loop {
+        let mut request = agent
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header(
+                "User-Agent",
+                concat!("rspotd-cli/", env!("CARGO_PKG_VERSION")),
+            );
+        if let Some(secret) = &webhook.secret {
+            request = request.header("X-Rspotd-Signature", signature(secret, body));
+        }
+        let error = match request.send(body) {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                if !(status.is_server_error() || status.as_u16() == 429) {
+                    return Err(Error::Delivery(format!(
+                        "Webhook {} rejected the payload: {}",
+                        webhook.url, status
+                    )));
+                }
+                format!("Webhook {} answered {}", webhook.url, status)
+            }
+            Err(err) => format!("Unable to reach webhook {}: {}", webhook.url, err),
+        };
+        if attempt >= webhook.retries {
+            return Err(Error::Delivery(error));
+        }
+        warn!("{}, retrying.", error);
+        sleep(Duration::from_secs(1 << attempt.min(6)));
+        attempt += 1;
+    }
+}
+
+// sha256=<hex HMAC-SHA256 of the body>, in the style of GitHub's X-Hub-Signature-256
+fn signature(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", digest)
+}
@@ -0,0 +1,149 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::time::Duration;
+use ureq::{http::Response, tls::TlsConfig, Agent, Body};
+
+/// The login flows `login` knows, see [`login`]
+pub const MODELS: [&str; 3] = ["basic", "surfboard", "touchstone"];
+
+/// Which web interface to log in to, and as whom
+pub struct WebLogin {
+    pub host: String,
+    /// One of [`MODELS`]
+    pub model: String,
+    /// The model's usual user if not given
+    pub user: Option<String>,
+    /// The scheme's usual port if not given
+    pub port: Option<u16>,
+    /// HTTPS even for models that are usually reached over plain HTTP
+    pub https: bool,
+    pub timeout: Duration,
+}
+
+/// How the web interface answered the password
+pub enum WebOutcome {
+    /// With the session cookie, as `name=value` pairs for a Cookie header, if one was set
+    Accepted(Option<String>),
+    /// With what the device said
+    Rejected(String),
+    /// The device didn't answer the way the model's login page should
+    Unexpected(String),
+}
+
+impl WebLogin {
+    /// The user given, or the one the model's web interface is usually logged in to as
+    pub fn user(&self) -> &str {
+        self.user.as_deref().unwrap_or(if self.model == "basic" {
+            "root"
+        } else {
+            "admin"
+        })
+    }
+}
+
+/// Log in to the web interface with the password. The flows are:
+///
+/// - `basic`: a GET of `/` with HTTP basic authentication
+/// - `surfboard`: the SB8200-style login over HTTPS, a GET of
+///   `/cmconnectionstatus.html?login_<credentials>` that answers with the token for the
+///   `credential` cookie
+/// - `touchstone`: the form POST to `/check.jst` of Touchstone gateways, which redirects to
+///   `at_a_glance.jst` once logged in
+///
+/// Devices serve self-signed certificates, so HTTPS certificates aren't verified. Fails if
+/// the device can't be reached at all.
+pub fn login(options: &WebLogin, password: &str) -> Result<WebOutcome, String> {
+    let agent: Agent = Agent::config_builder()
+        .timeout_global(Some(options.timeout))
+        .http_status_as_error(false)
+        .max_redirects(0)
+        .max_redirects_will_error(false)
+        .tls_config(TlsConfig::builder().disable_verification(true).build())
+        .user_agent(concat!("rspotd-cli/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .into();
+    let https = options.https || options.model == "surfboard";
+    let base = match (https, options.port) {
+        (true, None) => format!("https://{}", options.host),
+        (false, None) => format!("http://{}", options.host),
+        (true, Some(port)) => format!("https://{}:{}", options.host, port),
+        (false, Some(port)) => format!("http://{}:{}", options.host, port),
+    };
+    let credentials = STANDARD.encode(format!("{}:{}", options.user(), password));
+    let unreachable = |err: ureq::Error| err.to_string();
+    match options.model.as_str() {
+        "surfboard" => {
+            let mut response = agent
+                .get(format!(
+                    "{}/cmconnectionstatus.html?login_{}",
+                    base, credentials
+                ))
+                .header("Authorization", format!("Basic {}", credentials))
+                .call()
+                .map_err(unreachable)?;
+            let status = response.status();
+            let token = response
+                .body_mut()
+                .read_to_string()
+                .map_err(unreachable)?
+                .trim()
+                .to_string();
+            Ok(match status.as_u16() {
+                // the login page itself comes back when the password is wrong
+                200 if !token.is_empty() && !token.contains('<') => {
+                    WebOutcome::Accepted(Some(format!("credential={}", token)))
+                }
+                200 => WebOutcome::Rejected(String::from("the login page came back")),
+                401 | 403 => WebOutcome::Rejected(status.to_string()),
+                _ => unexpected(&response),
+            })
+        }
+        "touchstone" => {
+            let response = agent
+                .post(format!("{}/check.jst", base))
+                .send_form([("username", options.user()), ("password", password)])
+                .map_err(unreachable)?;
+            let location = response
+                .headers()
+                .get("location")
+                .and_then(|location| location.to_str().ok())
+                .unwrap_or_default();
+            Ok(if location.contains("at_a_glance") {
+                WebOutcome::Accepted(cookies(&response))
+            } else if response.status().is_redirection() || response.status().is_success() {
+                WebOutcome::Rejected(String::from("the login page came back"))
+            } else {
+                unexpected(&response)
+            })
+        }
+        _ => {
+            let response = agent
+                .get(format!("{}/", base))
+                .header("Authorization", format!("Basic {}", credentials))
+                .call()
+                .map_err(unreachable)?;
+            let status = response.status();
+            Ok(match status.as_u16() {
+                200..=399 => WebOutcome::Accepted(cookies(&response)),
+                401 | 403 => WebOutcome::Rejected(status.to_string()),
+                _ => unexpected(&response),
+            })
+        }
+    }
+}
+
+// the cookies set by the response, as they'd be sent back in a Cookie header
+fn cookies(response: &Response<Body>) -> Option<String> {
+    let cookies: Vec<&str> = response
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|cookie| cookie.to_str().ok())
+        .filter_map(|cookie| cookie.split(';').next())
+        .map(str::trim)
+        .collect();
+    (!cookies.is_empty()).then(|| cookies.join("; "))
+}
+
+fn unexpected(response: &Response<Body>) -> WebOutcome {
+    WebOutcome::Unexpected(format!("the web interface answered {}", response.status()))
+}
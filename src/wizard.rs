@@ -0,0 +1,82 @@
+use crate::dates::{resolve_date, try_parse_date};
+use crate::seed::validate_seed;
+use std::io::{stdin, stdout, IsTerminal, Write};
+
+/// What the user asked for in the wizard
+pub struct Answers {
+    /// None for the default seed
+    pub seed: Option<String>,
+    /// One date, or the first and last date of a range
+    pub dates: Vec<String>,
+    pub output: Option<String>,
+}
+
+/// Whether to ask instead of printing today's password for the default seed: only when
+/// nothing at all was given on the command line (`bare`) and someone is there to answer
+pub fn wanted(bare: bool) -> bool {
+    bare && stdin().is_terminal() && stdout().is_terminal()
+}
+
+/// Ask for the seed, the dates and where to write the passwords. Input ending early
+/// gives `None`.
+pub fn ask() -> Result<Option<Answers>, String> {
+    println!("No seed is configured, so a few questions first (see --help for every option).");
+    let seed = loop {
+        let seed = rpassword::prompt_password("Seed, hidden (leave empty for the default seed): ")
+            .map_err(|err| format!("Unable to read seed: {}", err))?;
+        if seed.is_empty() {
+            println!(
+                "Using the default seed; its passwords only work on devices that haven't been given their own seed."
+            );
+            break None;
+        }
+        match validate_seed(&seed) {
+            Ok(()) => break Some(seed),
+            Err(err) => println!("{}", err),
+        }
+    };
+    let dates = loop {
+        let Some(answer) = prompt("Date, or first and last date of a range [today]: ")? else {
+            return Ok(None);
+        };
+        let dates: Vec<String> = answer.split_whitespace().map(String::from).collect();
+        if dates.len() > 2 {
+            println!("Give one date, or two for a range.");
+            continue;
+        }
+        let invalid = dates
+            .iter()
+            .find_map(|date| try_parse_date(&resolve_date(date)).err());
+        match invalid {
+            Some(err) => println!("{}", err),
+            None => break dates,
+        }
+    };
+    let Some(output) = prompt("Write to file (leave empty to print): ")? else {
+        return Ok(None);
+    };
+    println!(
+        "Tip: store the seed with 'rspotd-cli seed store' and 'rspotd-cli seed use', or set it in the config file, to skip these questions."
+    );
+    println!();
+    Ok(Some(Answers {
+        seed,
+        dates,
+        output: (!output.is_empty()).then_some(output),
+    }))
+}
+
+// the trimmed answer, or None if input ends
+fn prompt(question: &str) -> Result<Option<String>, String> {
+    print!("{}", question);
+    let _ = stdout().flush();
+    let mut answer = String::new();
+    match stdin().read_line(&mut answer) {
+        Ok(0) => {
+            println!();
+            Ok(None)
+        }
+        Ok(_) => Ok(Some(answer.trim().to_string())),
+        Err(err) => Err(format!("Unable to read answer: {}", err)),
+    }
+}
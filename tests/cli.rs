@@ -0,0 +1,111 @@
+use std::{env, ffi::OsString, fs, path::PathBuf, process::ExitCode};
+
+// an empty config file, so the tests don't pick up the one of whoever runs them
+fn empty_config() -> PathBuf {
+    let path = env::temp_dir().join("rspotd-cli-tests.toml");
+    fs::write(&path, "").unwrap();
+    path
+}
+
+// run the command line with the given arguments, returning the exit code, stdout and stderr
+fn rspotd(args: &[&str]) -> (ExitCode, String, String) {
+    let mut argv: Vec<OsString> = vec!["rspotd-cli".into(), "-c".into(), empty_config().into()];
+    argv.extend(args.iter().map(OsString::from));
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    let code = rspotd_cli::run(argv, &mut stdout, &mut stderr);
+    (
+        code,
+        String::from_utf8(stdout).unwrap(),
+        String::from_utf8(stderr).unwrap(),
+    )
+}
+
+fn output(args: &[&str]) -> String {
+    let (code, stdout, stderr) = rspotd(args);
+    assert_eq!(code, ExitCode::SUCCESS, "{}", stderr);
+    stdout
+}
+
+#[test]
+fn default_seed() {
+    assert_eq!(output(&["-d", "2024-04-21", "-q"]), "L8UIT4FUX4\n");
+}
+
+#[test]
+fn formats() {
+    let single = ["-s", "ASDF", "-d", "2024-04-21", "-f"];
+    let cases = [
+        ("json", "[\n  {\n    \"date\": \"2024-04-21\",\n    \"password\": \"08GY8HS1RH\"\n  }\n]\n"),
+        ("ndjson", "{\"date\":\"2024-04-21\",\"password\":\"08GY8HS1RH\"}\n"),
+        ("csv", "2024-04-21,08GY8HS1RH\n"),
+        ("tsv", "2024-04-21\t08GY8HS1RH\n"),
+        ("yaml", "\"2024-04-21\": \"08GY8HS1RH\"\n"),
+        (
+            "markdown",
+            "| Date       | Password   |\n|------------|------------|\n| 2024-04-21 | 08GY8HS1RH |\n",
+        ),
+        ("env", "POTD=08GY8HS1RH\nPOTD_DATE=2024-04-21\n"),
+    ];
+    for (format, expected) in cases {
+        let args: Vec<&str> = single.iter().copied().chain([format]).collect();
+        assert_eq!(output(&args), expected, "--format {}", format);
+    }
+}
+
+#[test]
+fn range() {
+    assert_eq!(
+        output(&["-s", "ABCD", "-r", "2024-04-20", "2024-04-22", "-f", "csv"]),
+        "2024-04-20,7XGLSLIF4J\n2024-04-21,PDX0ZAX369\n2024-04-22,AW3C9DB59V\n"
+    );
+}
+
+#[test]
+fn multi_seed_range() {
+    let args: Vec<&str> = "-s ASDF -s ABCD -r 2024-04-20 2024-04-22 -f csv --header"
+        .split(' ')
+        .collect();
+    // grouped by seed, each labelled with its fingerprint rather than the seed itself
+    assert_eq!(
+        output(&args),
+        "seed,date,password\n\
+         99b3bcf6,2024-04-20,XWR7VE8PG2\n\
+         99b3bcf6,2024-04-21,08GY8HS1RH\n\
+         99b3bcf6,2024-04-22,XVG2A1Y6B8\n\
+         e12e115a,2024-04-20,7XGLSLIF4J\n\
+         e12e115a,2024-04-21,PDX0ZAX369\n\
+         e12e115a,2024-04-22,AW3C9DB59V\n"
+    );
+}
+
+#[test]
+fn usage_error() {
+    let (code, stdout, stderr) = rspotd(&["--no-such-option"]);
+    assert_eq!(code, ExitCode::from(2));
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("--no-such-option"), "{}", stderr);
+}
+
+#[test]
+fn invalid_date() {
+    let (code, _, stderr) = rspotd(&["-d", "2024-13-01"]);
+    assert_eq!(code, ExitCode::from(3));
+    assert!(stderr.contains("out of range"), "{}", stderr);
+}
+
+#[test]
+fn invalid_seed() {
+    let (code, _, stderr) = rspotd(&["-s", "AB", "-d", "2024-04-21"]);
+    assert_eq!(code, ExitCode::from(3));
+    assert!(stderr.contains("too short"), "{}", stderr);
+}
+
+#[test]
+fn unreadable_dates_file() {
+    let missing = env::temp_dir()
+        .join("rspotd-cli-tests-missing")
+        .join("dates.txt");
+    let (code, _, stderr) = rspotd(&["--dates-from", missing.to_str().unwrap()]);
+    assert_eq!(code, ExitCode::from(4));
+    assert!(stderr.contains("Unable to read dates"), "{}", stderr);
+}
@@ -0,0 +1,155 @@
+//! The password-of-the-day generator for a web page, built with wasm-pack. Ranges are generated
+//! and rendered by rspotd-core, the same code the command line uses, so none of the command
+//! line's other dependencies end up in the module.
+
+use chrono::NaiveDate;
+use rspotd::vals::DEFAULT_SEED;
+use rspotd_core::format::{self, Entry, FormatOptions, FORMATS};
+use wasm_bindgen::prelude::*;
+
+/// The password for a date in YYYY-MM-DD form, with the default seed if none is given
+#[wasm_bindgen]
+pub fn generate(date: &str, seed: Option<String>) -> Result<String, JsError> {
+    let seed = seed.as_deref().unwrap_or(DEFAULT_SEED);
+    validate_seed(seed)?;
+    parse_date(date)?;
+    rspotd::generate(date, seed).map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// The passwords for every date from start to end, inclusive and in date order, with the
+/// default seed if none is given; a range may be any number of years long
+#[wasm_bindgen(js_name = generateRange)]
+pub fn generate_range(
+    start: &str,
+    end: &str,
+    seed: Option<String>,
+) -> Result<Vec<String>, JsError> {
+    Ok(entries(start, end, seed)?
+        .into_iter()
+        .map(|entry| entry.potd)
+        .collect())
+}
+
+/// The formats `formatRange` and `formatDocument` accept, the same as the command line's
+/// --format except pdf, which can't be built for WebAssembly
+#[wasm_bindgen]
+pub fn formats() -> Vec<String> {
+    FORMATS
+        .iter()
+        .filter(|format| **format != "pdf")
+        .map(|format| format.to_string())
+        .collect()
+}
+
+/// The passwords for every date from start to end rendered as text in one of the
+/// command line's formats, e.g. csv or markdown. `dateFormat` is a strftime format, YYYY-MM-DD
+/// if not given, and `header` adds a heading row to the formats that have one.
+#[wasm_bindgen(js_name = formatRange)]
+pub fn format_range(
+    start: &str,
+    end: &str,
+    seed: Option<String>,
+    format: &str,
+    date_format: Option<String>,
+    header: Option<bool>,
+) -> Result<String, JsError> {
+    let options = options(format, date_format, header)?;
+    if format::binary_format(format) {
+        return Err(JsError::new(&format!(
+            "{} is a binary format; use formatDocument.",
+            format
+        )));
+    }
+    format::format_potd_range(&options, &entries(start, end, seed)?)
+        .map_err(|err| JsError::new(&err))
+}
+
+/// The passwords for every date from start to end as an xlsx spreadsheet, to offer as a
+/// download; the other arguments are as for `formatRange`
+#[wasm_bindgen(js_name = formatDocument)]
+pub fn format_document(
+    start: &str,
+    end: &str,
+    seed: Option<String>,
+    format: &str,
+    date_format: Option<String>,
+    header: Option<bool>,
+) -> Result<Vec<u8>, JsError> {
+    let options = options(format, date_format, header)?;
+    format::format_document(&options, &entries(start, end, seed)?).map_err(|err| JsError::new(&err))
+}
+
+/// The DES representation of the seed, for a modem's configuration file
+#[wasm_bindgen(js_name = seedToDes)]
+pub fn seed_to_des(seed: &str) -> Result<String, JsError> {
+    rspotd::seed_to_des(seed).map_err(|err| JsError::new(&err.to_string()))
+}
+
+// every date from start to end with its password
+fn entries(start: &str, end: &str, seed: Option<String>) -> Result<Vec<Entry>, JsError> {
+    let seed = seed.as_deref().unwrap_or(DEFAULT_SEED);
+    validate_seed(seed)?;
+    let (start, end) = (parse_date(start)?, parse_date(end)?);
+    if end < start {
+        return Err(JsError::new(
+            "Invalid date range. Beginning date must not occur after end date.",
+        ));
+    }
+    let passwords = rspotd_core::range::generate_range(start, end, seed)
+        .map_err(|err| JsError::new(&err.to_string()))?;
+    Ok(passwords
+        .into_iter()
+        .map(|(date, potd)| Entry {
+            seed: seed.to_string(),
+            date,
+            potd,
+        })
+        .collect())
+}
+
+// rspotd panics on a seed or date it can't use, which traps the module, so they're checked
+// as the command line does first
+fn parse_date(date: &str) -> Result<NaiveDate, JsError> {
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+        JsError::new(&format!(
+            "Unable to parse date '{}', expected YYYY-MM-DD.",
+            date
+        ))
+    })?;
+    rspotd_core::validate_date(parsed, date).map_err(|err| JsError::new(&err))?;
+    Ok(parsed)
+}
+
+fn validate_seed(seed: &str) -> Result<(), JsError> {
+    rspotd_core::validate_seed(seed).map_err(|err| JsError::new(&err))
+}
+
+// the command line's defaults for everything that can't be chosen here
+fn options(
+    format: &str,
+    date_format: Option<String>,
+    header: Option<bool>,
+) -> Result<FormatOptions, JsError> {
+    let formats = formats();
+    if !formats.iter().any(|known| known == format) {
+        return Err(JsError::new(&format!(
+            "Unknown format '{}', must be one of: {}.",
+            format,
+            formats.join(", ")
+        )));
+    }
+    let date_format = date_format.unwrap_or_else(|| String::from("%Y-%m-%d"));
+    format::validate_date_format(&date_format).map_err(|err| JsError::new(&err))?;
+    Ok(FormatOptions {
+        format: format.to_string(),
+        date_format,
+        locale: None,
+        header: header.unwrap_or(false),
+        title: None,
+        password_only: false,
+        template: None,
+        color: false,
+        grouped: false,
+        export: false,
+    })
+}